@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::math::Rng;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EntityId(pub u64);
@@ -11,6 +12,10 @@ impl EntityId {
 	pub fn idx(&self) -> u64 {
 		self.0
 	}
+
+	pub fn rng(&self, world: &Rng) -> Rng {
+		world.fork(self.0)
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
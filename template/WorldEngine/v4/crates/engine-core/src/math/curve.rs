@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use super::Vec3;
+
+const CLOSEST_SAMPLES: usize = 64;
+const CLOSEST_REFINE: usize = 24;
+
+pub trait Curve {
+	fn at(&self, t: f32) -> Vec3;
+	fn deriv(&self, t: f32) -> Vec3;
+
+	fn dir(&self, t: f32) -> Vec3 {
+		self.deriv(t).nrm()
+	}
+
+	fn approx_len(&self, samples: usize) -> f32 {
+		let n = samples.max(1);
+		let mut prv = self.at(0.0);
+		let mut total = 0.0;
+		for i in 1..=n {
+			let p = self.at(i as f32 / n as f32);
+			total += p.dist(&prv);
+			prv = p;
+		}
+		total
+	}
+
+	fn closest_t(&self, p: &Vec3) -> f32 {
+		let mut best_t = 0.0;
+		let mut best_d = f32::MAX;
+		for i in 0..=CLOSEST_SAMPLES {
+			let t = i as f32 / CLOSEST_SAMPLES as f32;
+			let d = self.at(t).sub(p).len_sq();
+			if d < best_d {
+				best_d = d;
+				best_t = t;
+			}
+		}
+		let step = 1.0 / CLOSEST_SAMPLES as f32;
+		let mut lo = (best_t - step).max(0.0);
+		let mut hi = (best_t + step).min(1.0);
+		for _ in 0..CLOSEST_REFINE {
+			let m1 = lo + (hi - lo) / 3.0;
+			let m2 = hi - (hi - lo) / 3.0;
+			if self.at(m1).sub(p).len_sq() < self.at(m2).sub(p).len_sq() {
+				hi = m2;
+			} else {
+				lo = m1;
+			}
+		}
+		(lo + hi) * 0.5
+	}
+
+	fn closest_point(&self, p: &Vec3) -> Vec3 {
+		self.at(self.closest_t(p))
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Bezier2 {
+	pub p0: Vec3,
+	pub p1: Vec3,
+	pub p2: Vec3,
+}
+
+impl Bezier2 {
+	pub fn new(p0: Vec3, p1: Vec3, p2: Vec3) -> Self {
+		Self { p0, p1, p2 }
+	}
+}
+
+impl Curve for Bezier2 {
+	fn at(&self, t: f32) -> Vec3 {
+		let u = 1.0 - t;
+		self.p0.mul(u * u)
+			.add(&self.p1.mul(2.0 * u * t))
+			.add(&self.p2.mul(t * t))
+	}
+
+	fn deriv(&self, t: f32) -> Vec3 {
+		let u = 1.0 - t;
+		self.p1.sub(&self.p0).mul(2.0 * u)
+			.add(&self.p2.sub(&self.p1).mul(2.0 * t))
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Bezier3 {
+	pub p0: Vec3,
+	pub p1: Vec3,
+	pub p2: Vec3,
+	pub p3: Vec3,
+}
+
+impl Bezier3 {
+	pub fn new(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> Self {
+		Self { p0, p1, p2, p3 }
+	}
+
+	pub fn split(&self, t: f32) -> (Self, Self) {
+		let a = self.p0.lrp(&self.p1, t);
+		let b = self.p1.lrp(&self.p2, t);
+		let c = self.p2.lrp(&self.p3, t);
+		let ab = a.lrp(&b, t);
+		let bc = b.lrp(&c, t);
+		let m = ab.lrp(&bc, t);
+		(Self::new(self.p0, a, ab, m), Self::new(m, bc, c, self.p3))
+	}
+}
+
+impl Curve for Bezier3 {
+	fn at(&self, t: f32) -> Vec3 {
+		let u = 1.0 - t;
+		self.p0.mul(u * u * u)
+			.add(&self.p1.mul(3.0 * u * u * t))
+			.add(&self.p2.mul(3.0 * u * t * t))
+			.add(&self.p3.mul(t * t * t))
+	}
+
+	fn deriv(&self, t: f32) -> Vec3 {
+		let u = 1.0 - t;
+		self.p1.sub(&self.p0).mul(3.0 * u * u)
+			.add(&self.p2.sub(&self.p1).mul(6.0 * u * t))
+			.add(&self.p3.sub(&self.p2).mul(3.0 * t * t))
+	}
+}
+
+fn hermite(p0: &Vec3, m0: &Vec3, p1: &Vec3, m1: &Vec3, t: f32) -> Vec3 {
+	let t2 = t * t;
+	let t3 = t2 * t;
+	p0.mul(2.0 * t3 - 3.0 * t2 + 1.0)
+		.add(&m0.mul(t3 - 2.0 * t2 + t))
+		.add(&p1.mul(-2.0 * t3 + 3.0 * t2))
+		.add(&m1.mul(t3 - t2))
+}
+
+fn hermite_deriv(p0: &Vec3, m0: &Vec3, p1: &Vec3, m1: &Vec3, t: f32) -> Vec3 {
+	let t2 = t * t;
+	p0.mul(6.0 * t2 - 6.0 * t)
+		.add(&m0.mul(3.0 * t2 - 4.0 * t + 1.0))
+		.add(&p1.mul(-6.0 * t2 + 6.0 * t))
+		.add(&m1.mul(3.0 * t2 - 2.0 * t))
+}
+
+fn seg_of(t: f32, segs: usize) -> (usize, f32) {
+	let f = t.clamp(0.0, 1.0) * segs as f32;
+	let i = (f.floor() as usize).min(segs - 1);
+	(i, f - i as f32)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Hermite {
+	pub pts: Vec<Vec3>,
+	pub tans: Vec<Vec3>,
+}
+
+impl Hermite {
+	pub fn new(pts: Vec<Vec3>, tans: Vec<Vec3>) -> Self {
+		Self { pts, tans }
+	}
+
+	pub fn seg_cnt(&self) -> usize {
+		self.pts.len().min(self.tans.len()).saturating_sub(1)
+	}
+}
+
+impl Curve for Hermite {
+	fn at(&self, t: f32) -> Vec3 {
+		let segs = self.seg_cnt();
+		if segs == 0 {
+			return self.pts.first().copied().unwrap_or(Vec3::ZERO);
+		}
+		let (i, u) = seg_of(t, segs);
+		hermite(&self.pts[i], &self.tans[i], &self.pts[i + 1], &self.tans[i + 1], u)
+	}
+
+	fn deriv(&self, t: f32) -> Vec3 {
+		let segs = self.seg_cnt();
+		if segs == 0 {
+			return Vec3::ZERO;
+		}
+		let (i, u) = seg_of(t, segs);
+		hermite_deriv(&self.pts[i], &self.tans[i], &self.pts[i + 1], &self.tans[i + 1], u)
+			.mul(segs as f32)
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatmullRom {
+	pub pts: Vec<Vec3>,
+	pub closed: bool,
+	pub tension: f32,
+}
+
+impl CatmullRom {
+	pub fn new(pts: Vec<Vec3>) -> Self {
+		Self {
+			pts,
+			closed: false,
+			tension: 0.5,
+		}
+	}
+
+	pub fn with_closed(mut self, closed: bool) -> Self {
+		self.closed = closed;
+		self
+	}
+
+	pub fn with_tension(mut self, tension: f32) -> Self {
+		self.tension = tension;
+		self
+	}
+
+	pub fn seg_cnt(&self) -> usize {
+		let n = self.pts.len();
+		if n < 2 {
+			0
+		} else if self.closed {
+			n
+		} else {
+			n - 1
+		}
+	}
+
+	fn pt(&self, i: isize) -> Vec3 {
+		let n = self.pts.len() as isize;
+		let idx = if self.closed {
+			i.rem_euclid(n)
+		} else {
+			i.clamp(0, n - 1)
+		};
+		self.pts[idx as usize]
+	}
+
+	fn seg(&self, t: f32) -> Option<(Vec3, Vec3, Vec3, Vec3, f32)> {
+		let segs = self.seg_cnt();
+		if segs == 0 {
+			return None;
+		}
+		let (i, u) = seg_of(t, segs);
+		let i = i as isize;
+		let p0 = self.pt(i - 1);
+		let p1 = self.pt(i);
+		let p2 = self.pt(i + 1);
+		let p3 = self.pt(i + 2);
+		let m1 = p2.sub(&p0).mul(self.tension);
+		let m2 = p3.sub(&p1).mul(self.tension);
+		Some((p1, m1, p2, m2, u))
+	}
+}
+
+impl Curve for CatmullRom {
+	fn at(&self, t: f32) -> Vec3 {
+		match self.seg(t) {
+			Some((p1, m1, p2, m2, u)) => hermite(&p1, &m1, &p2, &m2, u),
+			None => self.pts.first().copied().unwrap_or(Vec3::ZERO),
+		}
+	}
+
+	fn deriv(&self, t: f32) -> Vec3 {
+		match self.seg(t) {
+			Some((p1, m1, p2, m2, u)) => hermite_deriv(&p1, &m1, &p2, &m2, u).mul(self.seg_cnt() as f32),
+			None => Vec3::ZERO,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArcLen {
+	lut: Vec<f32>,
+}
+
+impl ArcLen {
+	pub fn new<C: Curve + ?Sized>(curve: &C, samples: usize) -> Self {
+		let n = samples.max(1);
+		let mut lut = Vec::with_capacity(n + 1);
+		lut.push(0.0);
+		let mut prv = curve.at(0.0);
+		let mut total = 0.0;
+		for i in 1..=n {
+			let p = curve.at(i as f32 / n as f32);
+			total += p.dist(&prv);
+			lut.push(total);
+			prv = p;
+		}
+		Self { lut }
+	}
+
+	pub fn len(&self) -> f32 {
+		self.lut.last().copied().unwrap_or(0.0)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() <= 0.0
+	}
+
+	pub fn t_at(&self, dist: f32) -> f32 {
+		let total = self.len();
+		if total <= 0.0 || self.lut.len() < 2 {
+			return 0.0;
+		}
+		let d = dist.clamp(0.0, total);
+		let hi = self.lut.partition_point(|&l| l < d).clamp(1, self.lut.len() - 1);
+		let lo = hi - 1;
+		let span = self.lut[hi] - self.lut[lo];
+		let f = if span > 0.0 { (d - self.lut[lo]) / span } else { 0.0 };
+		(lo as f32 + f) / (self.lut.len() - 1) as f32
+	}
+
+	pub fn dist_at(&self, t: f32) -> f32 {
+		if self.lut.len() < 2 {
+			return 0.0;
+		}
+		let segs = self.lut.len() - 1;
+		let (i, u) = seg_of(t, segs);
+		self.lut[i] + (self.lut[i + 1] - self.lut[i]) * u
+	}
+
+	pub fn at<C: Curve + ?Sized>(&self, curve: &C, dist: f32) -> Vec3 {
+		curve.at(self.t_at(dist))
+	}
+
+	pub fn at_norm<C: Curve + ?Sized>(&self, curve: &C, u: f32) -> Vec3 {
+		curve.at(self.t_at(u * self.len()))
+	}
+}
@@ -0,0 +1,316 @@
+#[cfg(feature = "deterministic")]
+mod soft {
+	use std::f64::consts::{FRAC_PI_2, FRAC_PI_6, LN_2, SQRT_2};
+
+	const PIO2_HI: f64 = 1.570_796_326_734_125_6;
+	const PIO2_LO: f64 = 6.077_100_506_506_192e-11;
+	const TAN_PI_12: f64 = 0.267_949_192_431_122_7;
+	const SQRT_3: f64 = 1.732_050_807_568_877_2;
+
+	fn sin_poly(x: f64) -> f64 {
+		let x2 = x * x;
+		let mut term = x;
+		let mut sum = x;
+		let mut n = 1.0;
+		for _ in 0..7 {
+			term *= -x2 / ((n + 1.0) * (n + 2.0));
+			sum += term;
+			n += 2.0;
+		}
+		sum
+	}
+
+	fn cos_poly(x: f64) -> f64 {
+		let x2 = x * x;
+		let mut term = 1.0;
+		let mut sum = 1.0;
+		let mut n = 0.0;
+		for _ in 0..8 {
+			term *= -x2 / ((n + 1.0) * (n + 2.0));
+			sum += term;
+			n += 2.0;
+		}
+		sum
+	}
+
+	fn reduce(x: f64) -> (i64, f64) {
+		let k = (x / FRAC_PI_2).round();
+		(k as i64, x - k * PIO2_HI - k * PIO2_LO)
+	}
+
+	pub fn sin(x: f64) -> f64 {
+		let (k, r) = reduce(x);
+		match k.rem_euclid(4) {
+			0 => sin_poly(r),
+			1 => cos_poly(r),
+			2 => -sin_poly(r),
+			_ => -cos_poly(r),
+		}
+	}
+
+	pub fn cos(x: f64) -> f64 {
+		let (k, r) = reduce(x);
+		match k.rem_euclid(4) {
+			0 => cos_poly(r),
+			1 => -sin_poly(r),
+			2 => -cos_poly(r),
+			_ => sin_poly(r),
+		}
+	}
+
+	fn atan_poly(x: f64) -> f64 {
+		let x2 = x * x;
+		let mut pow = x;
+		let mut sum = x;
+		let mut sign = -1.0;
+		let mut n = 3.0;
+		for _ in 0..10 {
+			pow *= x2;
+			sum += sign * pow / n;
+			sign = -sign;
+			n += 2.0;
+		}
+		sum
+	}
+
+	pub fn atan(x: f64) -> f64 {
+		if x.is_nan() {
+			return x;
+		}
+		let neg = x.is_sign_negative();
+		let mut a = x.abs();
+		let inv = a > 1.0;
+		if inv {
+			a = 1.0 / a;
+		}
+		let r = if a > TAN_PI_12 {
+			FRAC_PI_6 + atan_poly((a * SQRT_3 - 1.0) / (a + SQRT_3))
+		} else {
+			atan_poly(a)
+		};
+		let r = if inv { FRAC_PI_2 - r } else { r };
+		if neg { -r } else { r }
+	}
+
+	pub fn atan2(y: f64, x: f64) -> f64 {
+		if x > 0.0 {
+			atan(y / x)
+		} else if x < 0.0 {
+			// sign bit, not y >= 0, so -0.0 lands on -pi like std
+			if !y.is_sign_negative() {
+				atan(y / x) + 2.0 * FRAC_PI_2
+			} else {
+				atan(y / x) - 2.0 * FRAC_PI_2
+			}
+		} else if y > 0.0 {
+			FRAC_PI_2
+		} else if y < 0.0 {
+			-FRAC_PI_2
+		} else if x.is_sign_negative() {
+			if y.is_sign_negative() { -2.0 * FRAC_PI_2 } else { 2.0 * FRAC_PI_2 }
+		} else {
+			y
+		}
+	}
+
+	pub fn exp(x: f64) -> f64 {
+		if x.is_nan() {
+			return x;
+		}
+		if x > 709.0 {
+			return f64::INFINITY;
+		}
+		if x < -708.0 {
+			return 0.0;
+		}
+		let k = (x / LN_2).round();
+		let r = x - k * LN_2;
+		let mut term = 1.0;
+		let mut sum = 1.0;
+		for i in 1..14 {
+			term *= r / i as f64;
+			sum += term;
+		}
+		sum * f64::from_bits(((k as i64 + 1023) as u64) << 52)
+	}
+
+	pub fn ln(x: f64) -> f64 {
+		if x.is_nan() || x < 0.0 {
+			return f64::NAN;
+		}
+		if x == 0.0 {
+			return f64::NEG_INFINITY;
+		}
+		if x.is_infinite() {
+			return x;
+		}
+		let bits = x.to_bits();
+		let mut e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+		let mut m = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | (1023u64 << 52));
+		if m > SQRT_2 {
+			m *= 0.5;
+			e += 1;
+		}
+		let s = (m - 1.0) / (m + 1.0);
+		let s2 = s * s;
+		let mut pow = s;
+		let mut sum = s;
+		let mut n = 3.0;
+		for _ in 0..9 {
+			pow *= s2;
+			sum += pow / n;
+			n += 2.0;
+		}
+		e as f64 * LN_2 + 2.0 * sum
+	}
+}
+
+#[cfg(feature = "deterministic")]
+pub fn sin(x: f32) -> f32 {
+	soft::sin(x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn cos(x: f32) -> f32 {
+	soft::cos(x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn tan(x: f32) -> f32 {
+	let x = x as f64;
+	(soft::sin(x) / soft::cos(x)) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn atan(x: f32) -> f32 {
+	soft::atan(x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn atan2(y: f32, x: f32) -> f32 {
+	soft::atan2(y as f64, x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn exp(x: f32) -> f32 {
+	soft::exp(x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn ln(x: f32) -> f32 {
+	soft::ln(x as f64) as f32
+}
+
+#[cfg(feature = "deterministic")]
+pub fn powf(x: f32, y: f32) -> f32 {
+	if y == 0.0 {
+		return 1.0;
+	}
+	if x == 0.0 {
+		return if y > 0.0 { 0.0 } else { f32::INFINITY };
+	}
+	if x < 0.0 {
+		// only whole exponents are defined for a negative base, odd ones keep the sign
+		if y.fract() != 0.0 {
+			return f32::NAN;
+		}
+		let r = soft::exp(y as f64 * soft::ln(-x as f64)) as f32;
+		return if y % 2.0 != 0.0 { -r } else { r };
+	}
+	soft::exp(y as f64 * soft::ln(x as f64)) as f32
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn sin(x: f32) -> f32 {
+	x.sin()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn cos(x: f32) -> f32 {
+	x.cos()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn tan(x: f32) -> f32 {
+	x.tan()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn atan(x: f32) -> f32 {
+	x.atan()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn atan2(y: f32, x: f32) -> f32 {
+	y.atan2(x)
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn exp(x: f32) -> f32 {
+	x.exp()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn ln(x: f32) -> f32 {
+	x.ln()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn powf(x: f32, y: f32) -> f32 {
+	x.powf(y)
+}
+
+// IEEE 754 requires sqrt to be correctly rounded, so the hardware result is
+// already bit-identical between native and wasm builds.
+pub fn sqrt(x: f32) -> f32 {
+	x.sqrt()
+}
+
+#[cfg(feature = "deterministic")]
+pub fn acos(x: f32) -> f32 {
+	let x = x.clamp(-1.0, 1.0);
+	atan2(sqrt((1.0 - x) * (1.0 + x)), x)
+}
+
+#[cfg(feature = "deterministic")]
+pub fn asin(x: f32) -> f32 {
+	let x = x.clamp(-1.0, 1.0);
+	atan2(x, sqrt((1.0 - x) * (1.0 + x)))
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn acos(x: f32) -> f32 {
+	x.clamp(-1.0, 1.0).acos()
+}
+
+#[cfg(not(feature = "deterministic"))]
+pub fn asin(x: f32) -> f32 {
+	x.clamp(-1.0, 1.0).asin()
+}
+
+pub fn sin_cos(x: f32) -> (f32, f32) {
+	(sin(x), cos(x))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// both builds must agree with std on signs and domains, not just roughly on values
+	#[test]
+	fn atan2_signed_zero() {
+		let pi = std::f32::consts::PI;
+		assert!((atan2(-0.0, -1.0) + pi).abs() < 1e-6);
+		assert!((atan2(0.0, -1.0) - pi).abs() < 1e-6);
+		assert!((atan2(-0.0, -0.0) + pi).abs() < 1e-6);
+		assert!(atan2(-0.0, 1.0).is_sign_negative());
+	}
+
+	#[test]
+	fn powf_negative_base() {
+		assert!((powf(-2.0, 2.0) - 4.0).abs() < 1e-5);
+		assert!((powf(-2.0, 3.0) + 8.0).abs() < 1e-5);
+		assert!((powf(-2.0, -1.0) + 0.5).abs() < 1e-6);
+		assert!(powf(-2.0, 0.5).is_nan());
+	}
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+const BACK_C1: f32 = 1.70158;
+const BACK_C2: f32 = BACK_C1 * 1.525;
+const BACK_C3: f32 = BACK_C1 + 1.0;
+const ELASTIC_C4: f32 = (2.0 * PI) / 3.0;
+const ELASTIC_C5: f32 = (2.0 * PI) / 4.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Ease {
+	#[default]
+	Linear,
+	SmoothStep,
+	InQuad,
+	OutQuad,
+	InOutQuad,
+	InCubic,
+	OutCubic,
+	InOutCubic,
+	InQuart,
+	OutQuart,
+	InOutQuart,
+	InSine,
+	OutSine,
+	InOutSine,
+	InExpo,
+	OutExpo,
+	InOutExpo,
+	InCirc,
+	OutCirc,
+	InOutCirc,
+	InBack,
+	OutBack,
+	InOutBack,
+	InElastic,
+	OutElastic,
+	InOutElastic,
+	InBounce,
+	OutBounce,
+	InOutBounce,
+}
+
+fn out_bounce(t: f32) -> f32 {
+	const N1: f32 = 7.5625;
+	const D1: f32 = 2.75;
+	if t < 1.0 / D1 {
+		N1 * t * t
+	} else if t < 2.0 / D1 {
+		let t = t - 1.5 / D1;
+		N1 * t * t + 0.75
+	} else if t < 2.5 / D1 {
+		let t = t - 2.25 / D1;
+		N1 * t * t + 0.9375
+	} else {
+		let t = t - 2.625 / D1;
+		N1 * t * t + 0.984375
+	}
+}
+
+impl Ease {
+	pub fn apply(&self, t: f32) -> f32 {
+		let t = t.clamp(0.0, 1.0);
+		match self {
+			Ease::Linear => t,
+			Ease::SmoothStep => t * t * (3.0 - 2.0 * t),
+			Ease::InQuad => t * t,
+			Ease::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+			Ease::InOutQuad => {
+				if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) * 0.5 }
+			}
+			Ease::InCubic => t * t * t,
+			Ease::OutCubic => 1.0 - (1.0 - t).powi(3),
+			Ease::InOutCubic => {
+				if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) * 0.5 }
+			}
+			Ease::InQuart => t * t * t * t,
+			Ease::OutQuart => 1.0 - (1.0 - t).powi(4),
+			Ease::InOutQuart => {
+				if t < 0.5 { 8.0 * t.powi(4) } else { 1.0 - (-2.0 * t + 2.0).powi(4) * 0.5 }
+			}
+			Ease::InSine => 1.0 - (t * PI * 0.5).cos(),
+			Ease::OutSine => (t * PI * 0.5).sin(),
+			Ease::InOutSine => -((PI * t).cos() - 1.0) * 0.5,
+			Ease::InExpo => {
+				if t <= 0.0 { 0.0 } else { 2f32.powf(10.0 * t - 10.0) }
+			}
+			Ease::OutExpo => {
+				if t >= 1.0 { 1.0 } else { 1.0 - 2f32.powf(-10.0 * t) }
+			}
+			Ease::InOutExpo => {
+				if t <= 0.0 {
+					0.0
+				} else if t >= 1.0 {
+					1.0
+				} else if t < 0.5 {
+					2f32.powf(20.0 * t - 10.0) * 0.5
+				} else {
+					(2.0 - 2f32.powf(-20.0 * t + 10.0)) * 0.5
+				}
+			}
+			Ease::InCirc => 1.0 - (1.0 - t * t).sqrt(),
+			Ease::OutCirc => (1.0 - (t - 1.0) * (t - 1.0)).sqrt(),
+			Ease::InOutCirc => {
+				if t < 0.5 {
+					(1.0 - (1.0 - (2.0 * t).powi(2)).sqrt()) * 0.5
+				} else {
+					((1.0 - (-2.0 * t + 2.0).powi(2)).sqrt() + 1.0) * 0.5
+				}
+			}
+			Ease::InBack => BACK_C3 * t * t * t - BACK_C1 * t * t,
+			Ease::OutBack => 1.0 + BACK_C3 * (t - 1.0).powi(3) + BACK_C1 * (t - 1.0).powi(2),
+			Ease::InOutBack => {
+				if t < 0.5 {
+					(2.0 * t).powi(2) * ((BACK_C2 + 1.0) * 2.0 * t - BACK_C2) * 0.5
+				} else {
+					((2.0 * t - 2.0).powi(2) * ((BACK_C2 + 1.0) * (t * 2.0 - 2.0) + BACK_C2) + 2.0) * 0.5
+				}
+			}
+			Ease::InElastic => {
+				if t <= 0.0 || t >= 1.0 {
+					t
+				} else {
+					-(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * ELASTIC_C4).sin()
+				}
+			}
+			Ease::OutElastic => {
+				if t <= 0.0 || t >= 1.0 {
+					t
+				} else {
+					2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * ELASTIC_C4).sin() + 1.0
+				}
+			}
+			Ease::InOutElastic => {
+				if t <= 0.0 || t >= 1.0 {
+					t
+				} else if t < 0.5 {
+					-(2f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin()) * 0.5
+				} else {
+					2f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin() * 0.5 + 1.0
+				}
+			}
+			Ease::InBounce => 1.0 - out_bounce(1.0 - t),
+			Ease::OutBounce => out_bounce(t),
+			Ease::InOutBounce => {
+				if t < 0.5 {
+					(1.0 - out_bounce(1.0 - 2.0 * t)) * 0.5
+				} else {
+					(1.0 + out_bounce(2.0 * t - 1.0)) * 0.5
+				}
+			}
+		}
+	}
+
+	pub fn lrp(&self, a: f32, b: f32, t: f32) -> f32 {
+		a + (b - a) * self.apply(t)
+	}
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use super::Vec3;
+use super::{det, Vec3};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Mat4 {
@@ -45,8 +45,7 @@ impl Mat4 {
 	}
 
 	pub fn rot_x(rad: f32) -> Self {
-		let c = rad.cos();
-		let s = rad.sin();
+		let (s, c) = det::sin_cos(rad);
 		let mut r = Self::identity();
 		r.m[5] = c;
 		r.m[6] = s;
@@ -56,8 +55,7 @@ impl Mat4 {
 	}
 
 	pub fn rot_y(rad: f32) -> Self {
-		let c = rad.cos();
-		let s = rad.sin();
+		let (s, c) = det::sin_cos(rad);
 		let mut r = Self::identity();
 		r.m[0] = c;
 		r.m[2] = -s;
@@ -67,8 +65,7 @@ impl Mat4 {
 	}
 
 	pub fn rot_z(rad: f32) -> Self {
-		let c = rad.cos();
-		let s = rad.sin();
+		let (s, c) = det::sin_cos(rad);
 		let mut r = Self::identity();
 		r.m[0] = c;
 		r.m[1] = s;
@@ -98,7 +95,7 @@ impl Mat4 {
 	}
 
 	pub fn perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
-		let f = 1.0 / (fov / 2.0).tan();
+		let f = 1.0 / det::tan(fov / 2.0);
 		let nf = 1.0 / (near - far);
 		Self {
 			m: [
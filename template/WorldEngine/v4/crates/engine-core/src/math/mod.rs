@@ -2,8 +2,17 @@ mod vec3;
 mod mat4;
 mod quat;
 mod noise;
+mod curve;
+mod ease;
+mod transform;
+pub mod rng;
+pub mod det;
 
 pub use vec3::Vec3;
 pub use mat4::Mat4;
 pub use quat::Quat;
-pub use noise::Perlin;
+pub use noise::{Perlin, NoiseGrid};
+pub use rng::Rng;
+pub use curve::{Curve, Bezier2, Bezier3, CatmullRom, Hermite, ArcLen};
+pub use ease::Ease;
+pub use transform::Transform;
@@ -1,4 +1,56 @@
 use serde::{Deserialize, Serialize};
+use super::{Rng, Vec3};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NoiseGrid {
+	pub origin: Vec3,
+	pub step: f32,
+	pub w: usize,
+	pub h: usize,
+	pub d: usize,
+	pub octaves: u32,
+	pub persistence: f32,
+}
+
+impl NoiseGrid {
+	pub fn new2d(ox: f32, oy: f32, step: f32, w: usize, h: usize) -> Self {
+		Self {
+			origin: Vec3::new(ox, oy, 0.0),
+			step,
+			w,
+			h,
+			d: 1,
+			octaves: 1,
+			persistence: 0.5,
+		}
+	}
+
+	pub fn new3d(origin: Vec3, step: f32, w: usize, h: usize, d: usize) -> Self {
+		Self {
+			origin,
+			step,
+			w,
+			h,
+			d,
+			octaves: 1,
+			persistence: 0.5,
+		}
+	}
+
+	pub fn with_fbm(mut self, octaves: u32, persistence: f32) -> Self {
+		self.octaves = octaves.max(1);
+		self.persistence = persistence;
+		self
+	}
+
+	pub fn len(&self) -> usize {
+		self.w * self.h * self.d
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Perlin {
@@ -16,12 +68,7 @@ impl Perlin {
 	pub fn new(seed: u64) -> Self {
 		let mut perm = [0usize; 512];
 		let mut p: Vec<usize> = (0..256).collect();
-		let mut rng = seed;
-		for i in (1..256).rev() {
-			rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1);
-			let j = ((rng >> 33) as usize) % (i + 1);
-			p.swap(i, j);
-		}
+		Rng::new(seed).shuffle(&mut p);
 		for i in 0..256 {
 			perm[i] = p[i];
 			perm[i + 256] = p[i];
@@ -112,4 +159,160 @@ impl Perlin {
 	pub fn fbm2d(&self, x: f32, y: f32, octaves: u32, persistence: f32) -> f32 {
 		self.fbm3d(x, y, 0.0, octaves, persistence)
 	}
+
+	pub fn fill(&self, grid: &NoiseGrid, out: &mut [f32]) {
+		let mut i = 0;
+		for z in 0..grid.d {
+			let pz = grid.origin.z + z as f32 * grid.step;
+			for y in 0..grid.h {
+				let py = grid.origin.y + y as f32 * grid.step;
+				if i + grid.w > out.len() {
+					return;
+				}
+				self.fill_row(&mut out[i..i + grid.w], grid, py, pz);
+				i += grid.w;
+			}
+		}
+	}
+
+	pub fn sample_grid(&self, grid: &NoiseGrid) -> Vec<f32> {
+		let mut out = vec![0.0; grid.len()];
+		self.fill(grid, &mut out);
+		out
+	}
+
+	#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+	fn fill_row(&self, row: &mut [f32], grid: &NoiseGrid, py: f32, pz: f32) {
+		let (ox, step) = (grid.origin.x, grid.step);
+		let (octaves, persistence) = (grid.octaves.max(1), grid.persistence);
+		for (x, v) in row.iter_mut().enumerate() {
+			*v = self.fbm3d(ox + x as f32 * step, py, pz, octaves, persistence);
+		}
+	}
+
+	#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+	fn fill_row(&self, row: &mut [f32], grid: &NoiseGrid, py: f32, pz: f32) {
+		use core::arch::wasm32::*;
+		let (ox, step) = (grid.origin.x, grid.step);
+		let (octaves, persistence) = (grid.octaves.max(1), grid.persistence);
+		let mut x = 0;
+		let vy = f32x4_splat(py);
+		let vz = f32x4_splat(pz);
+		while x + 4 <= row.len() {
+			let fx = x as f32;
+			let vx = f32x4(
+				ox + fx * step,
+				ox + (fx + 1.0) * step,
+				ox + (fx + 2.0) * step,
+				ox + (fx + 3.0) * step,
+			);
+			let r = self.fbm3d_x4(vx, vy, vz, octaves, persistence);
+			row[x] = f32x4_extract_lane::<0>(r);
+			row[x + 1] = f32x4_extract_lane::<1>(r);
+			row[x + 2] = f32x4_extract_lane::<2>(r);
+			row[x + 3] = f32x4_extract_lane::<3>(r);
+			x += 4;
+		}
+		while x < row.len() {
+			row[x] = self.fbm3d(ox + x as f32 * step, py, pz, octaves, persistence);
+			x += 1;
+		}
+	}
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod simd {
+	use core::arch::wasm32::*;
+	use super::Perlin;
+
+	fn fade(t: v128) -> v128 {
+		let inner = f32x4_add(
+			f32x4_mul(t, f32x4_sub(f32x4_mul(t, f32x4_splat(6.0)), f32x4_splat(15.0))),
+			f32x4_splat(10.0),
+		);
+		f32x4_mul(f32x4_mul(f32x4_mul(t, t), t), inner)
+	}
+
+	fn lrp(a: v128, b: v128, t: v128) -> v128 {
+		f32x4_add(a, f32x4_mul(t, f32x4_sub(b, a)))
+	}
+
+	fn lanes(v: v128) -> [usize; 4] {
+		[
+			(i32x4_extract_lane::<0>(v) & 255) as usize,
+			(i32x4_extract_lane::<1>(v) & 255) as usize,
+			(i32x4_extract_lane::<2>(v) & 255) as usize,
+			(i32x4_extract_lane::<3>(v) & 255) as usize,
+		]
+	}
+
+	impl Perlin {
+		fn grad_x4(&self, h: [usize; 4], x: v128, y: v128, z: v128) -> v128 {
+			let g = [
+				&self.grad[h[0] & 15],
+				&self.grad[h[1] & 15],
+				&self.grad[h[2] & 15],
+				&self.grad[h[3] & 15],
+			];
+			let gx = f32x4(g[0][0], g[1][0], g[2][0], g[3][0]);
+			let gy = f32x4(g[0][1], g[1][1], g[2][1], g[3][1]);
+			let gz = f32x4(g[0][2], g[1][2], g[2][2], g[3][2]);
+			f32x4_add(f32x4_add(f32x4_mul(gx, x), f32x4_mul(gy, y)), f32x4_mul(gz, z))
+		}
+
+		pub(super) fn noise3d_x4(&self, x: v128, y: v128, z: v128) -> v128 {
+			let fx = f32x4_floor(x);
+			let fy = f32x4_floor(y);
+			let fz = f32x4_floor(z);
+			let xi = lanes(i32x4_trunc_sat_f32x4(fx));
+			let yi = lanes(i32x4_trunc_sat_f32x4(fy));
+			let zi = lanes(i32x4_trunc_sat_f32x4(fz));
+			let xf = f32x4_sub(x, fx);
+			let yf = f32x4_sub(y, fy);
+			let zf = f32x4_sub(z, fz);
+			let u = fade(xf);
+			let v = fade(yf);
+			let w = fade(zf);
+			let one = f32x4_splat(1.0);
+			let xf1 = f32x4_sub(xf, one);
+			let yf1 = f32x4_sub(yf, one);
+			let zf1 = f32x4_sub(zf, one);
+			let p = &self.perm;
+			let mut h = [[0usize; 4]; 8];
+			for l in 0..4 {
+				let (a, b, c) = (xi[l], yi[l], zi[l]);
+				h[0][l] = p[p[p[a] + b] + c];
+				h[1][l] = p[p[p[a + 1] + b] + c];
+				h[2][l] = p[p[p[a] + b + 1] + c];
+				h[3][l] = p[p[p[a + 1] + b + 1] + c];
+				h[4][l] = p[p[p[a] + b] + c + 1];
+				h[5][l] = p[p[p[a + 1] + b] + c + 1];
+				h[6][l] = p[p[p[a] + b + 1] + c + 1];
+				h[7][l] = p[p[p[a + 1] + b + 1] + c + 1];
+			}
+			let x1 = lrp(self.grad_x4(h[0], xf, yf, zf), self.grad_x4(h[1], xf1, yf, zf), u);
+			let x2 = lrp(self.grad_x4(h[2], xf, yf1, zf), self.grad_x4(h[3], xf1, yf1, zf), u);
+			let y1 = lrp(x1, x2, v);
+			let x3 = lrp(self.grad_x4(h[4], xf, yf, zf1), self.grad_x4(h[5], xf1, yf, zf1), u);
+			let x4 = lrp(self.grad_x4(h[6], xf, yf1, zf1), self.grad_x4(h[7], xf1, yf1, zf1), u);
+			let y2 = lrp(x3, x4, v);
+			f32x4_mul(f32x4_add(lrp(y1, y2, w), one), f32x4_splat(0.5))
+		}
+
+		pub(super) fn fbm3d_x4(&self, x: v128, y: v128, z: v128, octaves: u32, persistence: f32) -> v128 {
+			let mut total = f32x4_splat(0.0);
+			let mut frequency = 1.0;
+			let mut amplitude = 1.0;
+			let mut max_value = 0.0;
+			for _ in 0..octaves {
+				let f = f32x4_splat(frequency);
+				let n = self.noise3d_x4(f32x4_mul(x, f), f32x4_mul(y, f), f32x4_mul(z, f));
+				total = f32x4_add(total, f32x4_mul(n, f32x4_splat(amplitude)));
+				max_value += amplitude;
+				amplitude *= persistence;
+				frequency *= 2.0;
+			}
+			f32x4_div(total, f32x4_splat(max_value))
+		}
+	}
 }
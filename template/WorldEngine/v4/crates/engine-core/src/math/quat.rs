@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use super::{Vec3, Mat4};
+use super::{det, Vec3, Mat4};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Quat {
@@ -26,23 +26,20 @@ impl Quat {
 
 	pub fn from_axis_angle(axis: &Vec3, rad: f32) -> Self {
 		let half = rad * 0.5;
-		let s = half.sin();
+		let s = det::sin(half);
 		let n = axis.nrm();
 		Self {
 			x: n.x * s,
 			y: n.y * s,
 			z: n.z * s,
-			w: half.cos(),
+			w: det::cos(half),
 		}
 	}
 
 	pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
-		let cx = (x * 0.5).cos();
-		let sx = (x * 0.5).sin();
-		let cy = (y * 0.5).cos();
-		let sy = (y * 0.5).sin();
-		let cz = (z * 0.5).cos();
-		let sz = (z * 0.5).sin();
+		let (sx, cx) = det::sin_cos(x * 0.5);
+		let (sy, cy) = det::sin_cos(y * 0.5);
+		let (sz, cz) = det::sin_cos(z * 0.5);
 		Self {
 			x: sx * cy * cz - cx * sy * sz,
 			y: cx * sy * cz + sx * cy * sz,
@@ -68,7 +65,7 @@ impl Quat {
 	}
 
 	pub fn len(&self) -> f32 {
-		(self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+		det::sqrt(self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w)
 	}
 
 	pub fn nrm(&self) -> Self {
@@ -127,11 +124,11 @@ impl Quat {
 				w: self.w + t * (other.w - self.w),
 			}.nrm();
 		}
-		let theta_0 = dot.acos();
+		let theta_0 = det::acos(dot);
 		let theta = theta_0 * t;
-		let sin_theta = theta.sin();
-		let sin_theta_0 = theta_0.sin();
-		let s0 = (theta_0 - theta).cos() - dot * sin_theta / sin_theta_0;
+		let sin_theta = det::sin(theta);
+		let sin_theta_0 = det::sin(theta_0);
+		let s0 = det::cos(theta_0 - theta) - dot * sin_theta / sin_theta_0;
 		let s1 = sin_theta / sin_theta_0;
 		Self {
 			x: self.x * s0 + other.x * s1,
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use super::{det, Vec3};
+
+const PCG_MUL: u64 = 6364136223846793005;
+
+fn mix(mut z: u64) -> u64 {
+	z = z.wrapping_add(0x9e3779b97f4a7c15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+	z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+	state: u64,
+	inc: u64,
+}
+
+impl Default for Rng {
+	fn default() -> Self {
+		Self::new(0)
+	}
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		Self::with_stream(seed, 0)
+	}
+
+	pub fn with_stream(seed: u64, stream: u64) -> Self {
+		let mut r = Self {
+			state: 0,
+			inc: (stream << 1) | 1,
+		};
+		r.next_u32();
+		r.state = r.state.wrapping_add(seed);
+		r.next_u32();
+		r
+	}
+
+	pub fn split(&mut self) -> Self {
+		let seed = self.next_u64();
+		let stream = self.next_u64();
+		Self::with_stream(seed, stream)
+	}
+
+	pub fn fork(&self, key: u64) -> Self {
+		Self::with_stream(mix(self.state ^ mix(key)), mix(self.inc.wrapping_add(key)))
+	}
+
+	pub fn fork3(&self, x: i32, y: i32, z: i32) -> Self {
+		let key = mix(x as u32 as u64)
+			^ mix((y as u32 as u64) << 21).rotate_left(17)
+			^ mix((z as u32 as u64) << 42).rotate_left(34);
+		self.fork(key)
+	}
+
+	pub fn next_u32(&mut self) -> u32 {
+		let old = self.state;
+		self.state = old.wrapping_mul(PCG_MUL).wrapping_add(self.inc);
+		let xs = (((old >> 18) ^ old) >> 27) as u32;
+		let rot = (old >> 59) as u32;
+		xs.rotate_right(rot)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		let hi = self.next_u32() as u64;
+		let lo = self.next_u32() as u64;
+		(hi << 32) | lo
+	}
+
+	pub fn next_f32(&mut self) -> f32 {
+		(self.next_u32() >> 8) as f32 * (1.0 / 16_777_216.0)
+	}
+
+	pub fn next_f64(&mut self) -> f64 {
+		(self.next_u64() >> 11) as f64 * (1.0 / 9_007_199_254_740_992.0)
+	}
+
+	pub fn next_bool(&mut self, p: f32) -> bool {
+		self.next_f32() < p
+	}
+
+	pub fn below(&mut self, n: u32) -> u32 {
+		if n == 0 {
+			return 0;
+		}
+		let threshold = n.wrapping_neg() % n;
+		loop {
+			let r = self.next_u32();
+			if r >= threshold {
+				return r % n;
+			}
+		}
+	}
+
+	pub fn below_u64(&mut self, n: u64) -> u64 {
+		if n == 0 {
+			return 0;
+		}
+		let threshold = n.wrapping_neg() % n;
+		loop {
+			let r = self.next_u64();
+			if r >= threshold {
+				return r % n;
+			}
+		}
+	}
+
+	pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+		lo + (hi - lo) * self.next_f32()
+	}
+
+	pub fn range_i32(&mut self, lo: i32, hi: i32) -> i32 {
+		if hi <= lo {
+			return lo;
+		}
+		let span = (hi as i64 - lo as i64) as u32;
+		(lo as i64 + self.below(span) as i64) as i32
+	}
+
+	pub fn range_usize(&mut self, lo: usize, hi: usize) -> usize {
+		if hi <= lo {
+			return lo;
+		}
+		let span = (hi - lo) as u64;
+		// spans that fit a u32 keep the u32 stream so existing seeds don't shift
+		match u32::try_from(span) {
+			Ok(n) => lo + self.below(n) as usize,
+			Err(_) => lo + self.below_u64(span) as usize,
+		}
+	}
+
+	pub fn normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+		let u1 = 1.0 - self.next_f32();
+		let u2 = self.next_f32();
+		let mag = det::sqrt(-2.0 * det::ln(u1));
+		mean + std_dev * mag * det::cos(std::f32::consts::TAU * u2)
+	}
+
+	pub fn weighted(&mut self, weights: &[f32]) -> Option<usize> {
+		let total: f32 = weights.iter().filter(|w| **w > 0.0).sum();
+		if total <= 0.0 {
+			return None;
+		}
+		let mut r = self.next_f32() * total;
+		let mut last = None;
+		for (i, &w) in weights.iter().enumerate() {
+			if w <= 0.0 {
+				continue;
+			}
+			if r < w {
+				return Some(i);
+			}
+			r -= w;
+			last = Some(i);
+		}
+		last
+	}
+
+	pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+		if items.is_empty() {
+			return None;
+		}
+		items.get(self.range_usize(0, items.len()))
+	}
+
+	pub fn choose_weighted<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T> {
+		let weights: Vec<f32> = items.iter().map(|(_, w)| *w).collect();
+		self.weighted(&weights).map(|i| &items[i].0)
+	}
+
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			let j = self.range_usize(0, i + 1);
+			items.swap(i, j);
+		}
+	}
+
+	pub fn in_sphere(&mut self, radius: f32) -> Vec3 {
+		loop {
+			let p = Vec3::new(
+				self.range_f32(-1.0, 1.0),
+				self.range_f32(-1.0, 1.0),
+				self.range_f32(-1.0, 1.0),
+			);
+			if p.len_sq() <= 1.0 {
+				return p.mul(radius);
+			}
+		}
+	}
+
+	pub fn on_sphere(&mut self, radius: f32) -> Vec3 {
+		let z = self.range_f32(-1.0, 1.0);
+		let a = self.range_f32(0.0, std::f32::consts::TAU);
+		let r = det::sqrt((1.0 - z * z).max(0.0));
+		let (s, c) = det::sin_cos(a);
+		Vec3::new(r * c, r * s, z).mul(radius)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// pcg32 reference output for seed 42, stream 54; world generation hangs off this stream
+	#[test]
+	fn pcg_stream() {
+		let mut r = Rng::with_stream(42, 54);
+		let out: Vec<u32> = (0..6).map(|_| r.next_u32()).collect();
+		assert_eq!(out, [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+	}
+
+	#[test]
+	fn fork_split() {
+		let base = Rng::new(7);
+		let mut f = base.fork(3);
+		assert_eq!((f.next_u64(), f.next_u64()), (0x168c4501735cd125, 0xfb0be92f4841f35b));
+		assert_eq!(base.fork3(-1, 2, 3).next_u64(), 0xebf6050a168f8af9);
+		let mut p = Rng::new(7);
+		let mut s = p.split();
+		assert_eq!((s.next_u64(), p.next_u64()), (0x1aaa61532661bc3a, 0xd363db3c2ed9566c));
+	}
+
+	#[cfg(target_pointer_width = "64")]
+	#[test]
+	fn wide_range() {
+		let mut r = Rng::new(1);
+		let out: Vec<usize> = (0..4).map(|_| r.range_usize(0, 1 << 32)).collect();
+		assert_eq!(out, [17903413, 194195274, 2354257974, 3102124240]);
+	}
+}
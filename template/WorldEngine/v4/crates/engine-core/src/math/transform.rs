@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use super::{Mat4, Quat, Vec3};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Transform {
+	pub pos: Vec3,
+	pub rot: Quat,
+	pub scl: Vec3,
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Self {
+			pos: Vec3::ZERO,
+			rot: Quat::identity(),
+			scl: Vec3::ONE,
+		}
+	}
+}
+
+impl Transform {
+	pub fn new(pos: Vec3) -> Self {
+		Self {
+			pos,
+			..Default::default()
+		}
+	}
+
+	pub fn with_rot(mut self, rot: Quat) -> Self {
+		self.rot = rot;
+		self
+	}
+
+	pub fn with_scl(mut self, scl: Vec3) -> Self {
+		self.scl = scl;
+		self
+	}
+
+	pub fn mul_point(&self, p: &Vec3) -> Vec3 {
+		let s = Vec3::new(p.x * self.scl.x, p.y * self.scl.y, p.z * self.scl.z);
+		self.pos.add(&self.rot.mul_vec3(&s))
+	}
+
+	pub fn lrp(&self, other: &Self, t: f32) -> Self {
+		Self {
+			pos: self.pos.lrp(&other.pos, t),
+			rot: self.rot.slerp(&other.rot, t),
+			scl: self.scl.lrp(&other.scl, t),
+		}
+	}
+
+	pub fn to_mat4(&self) -> Mat4 {
+		Mat4::scl(&self.scl)
+			.mul(&self.rot.to_mat4())
+			.mul(&Mat4::trs(&self.pos))
+	}
+}
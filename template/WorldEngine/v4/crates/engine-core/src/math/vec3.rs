@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use super::det;
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Vec3 {
@@ -55,7 +56,7 @@ impl Vec3 {
 	}
 
 	pub fn len(&self) -> f32 {
-		(self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+		det::sqrt(self.x * self.x + self.y * self.y + self.z * self.z)
 	}
 
 	pub fn len_sq(&self) -> f32 {
@@ -0,0 +1,304 @@
+use crate::math::Vec3;
+use super::AABB;
+
+const NULL: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct Node {
+	aabb: AABB,
+	parent: usize,
+	left: usize,
+	right: usize,
+	height: i32,
+	id: u64,
+}
+
+impl Node {
+	fn is_leaf(&self) -> bool {
+		self.left == NULL
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct AabbTree {
+	nodes: Vec<Node>,
+	root: usize,
+	free: Vec<usize>,
+	cnt: usize,
+	pub margin: f32,
+}
+
+impl Default for AabbTree {
+	fn default() -> Self {
+		Self::new(0.1)
+	}
+}
+
+impl AabbTree {
+	pub fn new(margin: f32) -> Self {
+		Self {
+			nodes: Vec::new(),
+			root: NULL,
+			free: Vec::new(),
+			cnt: 0,
+			margin,
+		}
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.cnt
+	}
+
+	pub fn height(&self) -> i32 {
+		if self.root == NULL { 0 } else { self.nodes[self.root].height }
+	}
+
+	pub fn clr(&mut self) {
+		self.nodes.clear();
+		self.free.clear();
+		self.root = NULL;
+		self.cnt = 0;
+	}
+
+	pub fn id(&self, proxy: usize) -> u64 {
+		self.nodes[proxy].id
+	}
+
+	pub fn fat(&self, proxy: usize) -> AABB {
+		self.nodes[proxy].aabb
+	}
+
+	pub fn insert(&mut self, id: u64, aabb: &AABB) -> usize {
+		let leaf = self.alloc(Node {
+			aabb: aabb.fatten(self.margin),
+			parent: NULL,
+			left: NULL,
+			right: NULL,
+			height: 0,
+			id,
+		});
+		self.insert_leaf(leaf);
+		self.cnt += 1;
+		leaf
+	}
+
+	pub fn remove(&mut self, proxy: usize) {
+		self.remove_leaf(proxy);
+		self.free.push(proxy);
+		self.cnt -= 1;
+	}
+
+	pub fn update(&mut self, proxy: usize, aabb: &AABB) -> bool {
+		if self.nodes[proxy].aabb.contains_aabb(aabb) {
+			return false;
+		}
+		self.remove_leaf(proxy);
+		self.nodes[proxy].aabb = aabb.fatten(self.margin);
+		self.insert_leaf(proxy);
+		true
+	}
+
+	pub fn query<F: FnMut(u64) -> bool>(&self, aabb: &AABB, mut f: F) {
+		if self.root == NULL {
+			return;
+		}
+		let mut stack = vec![self.root];
+		while let Some(i) = stack.pop() {
+			let node = &self.nodes[i];
+			if !node.aabb.intersects(aabb) {
+				continue;
+			}
+			if node.is_leaf() {
+				if !f(node.id) {
+					return;
+				}
+			} else {
+				stack.push(node.left);
+				stack.push(node.right);
+			}
+		}
+	}
+
+	// visits every live node with its height, leaves are height 0
+	pub fn each_node<F: FnMut(&AABB, i32)>(&self, mut f: F) {
+		if self.root == NULL {
+			return;
+		}
+		let mut stack = vec![self.root];
+		while let Some(i) = stack.pop() {
+			let node = &self.nodes[i];
+			f(&node.aabb, node.height);
+			if !node.is_leaf() {
+				stack.push(node.left);
+				stack.push(node.right);
+			}
+		}
+	}
+
+	pub fn ray<F: FnMut(u64) -> bool>(&self, o: &Vec3, d: &Vec3, max_t: f32, mut f: F) {
+		if self.root == NULL {
+			return;
+		}
+		let mut stack = vec![self.root];
+		while let Some(i) = stack.pop() {
+			let node = &self.nodes[i];
+			if !node.aabb.seg_overlaps(o, d, max_t) {
+				continue;
+			}
+			if node.is_leaf() {
+				if !f(node.id) {
+					return;
+				}
+			} else {
+				stack.push(node.left);
+				stack.push(node.right);
+			}
+		}
+	}
+
+	fn alloc(&mut self, node: Node) -> usize {
+		match self.free.pop() {
+			Some(i) => {
+				self.nodes[i] = node;
+				i
+			}
+			None => {
+				self.nodes.push(node);
+				self.nodes.len() - 1
+			}
+		}
+	}
+
+	fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+		if parent == NULL {
+			self.root = new;
+		} else if self.nodes[parent].left == old {
+			self.nodes[parent].left = new;
+		} else {
+			self.nodes[parent].right = new;
+		}
+	}
+
+	fn insert_leaf(&mut self, leaf: usize) {
+		if self.root == NULL {
+			self.root = leaf;
+			self.nodes[leaf].parent = NULL;
+			return;
+		}
+		let la = self.nodes[leaf].aabb;
+		let mut i = self.root;
+		while !self.nodes[i].is_leaf() {
+			let node = &self.nodes[i];
+			let area = node.aabb.area();
+			let comb = node.aabb.merge(&la).area();
+			let cost = 2.0 * comb;
+			let inherit = 2.0 * (comb - area);
+			let child_cost = |c: usize| {
+				let ch = &self.nodes[c];
+				let m = la.merge(&ch.aabb).area();
+				if ch.is_leaf() { m + inherit } else { m - ch.aabb.area() + inherit }
+			};
+			let (cl, cr) = (child_cost(node.left), child_cost(node.right));
+			if cost < cl && cost < cr {
+				break;
+			}
+			i = if cl < cr { node.left } else { node.right };
+		}
+		let sib = i;
+		let old_parent = self.nodes[sib].parent;
+		let parent = self.alloc(Node {
+			aabb: la.merge(&self.nodes[sib].aabb),
+			parent: old_parent,
+			left: sib,
+			right: leaf,
+			height: self.nodes[sib].height + 1,
+			id: 0,
+		});
+		self.replace_child(old_parent, sib, parent);
+		self.nodes[sib].parent = parent;
+		self.nodes[leaf].parent = parent;
+		self.fix_up(parent);
+	}
+
+	fn remove_leaf(&mut self, leaf: usize) {
+		if leaf == self.root {
+			self.root = NULL;
+			return;
+		}
+		let parent = self.nodes[leaf].parent;
+		let grand = self.nodes[parent].parent;
+		let sib = if self.nodes[parent].left == leaf { self.nodes[parent].right } else { self.nodes[parent].left };
+		self.replace_child(grand, parent, sib);
+		self.nodes[sib].parent = grand;
+		self.free.push(parent);
+		if grand != NULL {
+			self.fix_up(grand);
+		}
+	}
+
+	fn fix_up(&mut self, mut i: usize) {
+		while i != NULL {
+			i = self.balance(i);
+			let (l, r) = (self.nodes[i].left, self.nodes[i].right);
+			self.nodes[i].height = 1 + self.nodes[l].height.max(self.nodes[r].height);
+			self.nodes[i].aabb = self.nodes[l].aabb.merge(&self.nodes[r].aabb);
+			i = self.nodes[i].parent;
+		}
+	}
+
+	fn balance(&mut self, a: usize) -> usize {
+		if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+			return a;
+		}
+		let (b, c) = (self.nodes[a].left, self.nodes[a].right);
+		let bal = self.nodes[c].height - self.nodes[b].height;
+		if bal > 1 {
+			self.rotate(a, c, b)
+		} else if bal < -1 {
+			self.rotate(a, b, c)
+		} else {
+			a
+		}
+	}
+
+	fn rotate(&mut self, a: usize, up: usize, other: usize) -> usize {
+		let (f, g) = (self.nodes[up].left, self.nodes[up].right);
+		let a_parent = self.nodes[a].parent;
+		self.nodes[up].left = a;
+		self.nodes[up].parent = a_parent;
+		self.nodes[a].parent = up;
+		self.replace_child(a_parent, a, up);
+		let (keep, give) = if self.nodes[f].height > self.nodes[g].height { (f, g) } else { (g, f) };
+		self.nodes[up].right = keep;
+		if self.nodes[a].left == up {
+			self.nodes[a].left = give;
+		} else {
+			self.nodes[a].right = give;
+		}
+		self.nodes[give].parent = a;
+		self.nodes[a].aabb = self.nodes[other].aabb.merge(&self.nodes[give].aabb);
+		self.nodes[up].aabb = self.nodes[a].aabb.merge(&self.nodes[keep].aabb);
+		self.nodes[a].height = 1 + self.nodes[other].height.max(self.nodes[give].height);
+		self.nodes[up].height = 1 + self.nodes[a].height.max(self.nodes[keep].height);
+		up
+	}
+}
+
+pub fn sweep_pairs(aabbs: &[AABB]) -> Vec<(usize, usize)> {
+	let mut order: Vec<usize> = (0..aabbs.len()).collect();
+	order.sort_unstable_by(|&a, &b| aabbs[a].min.x.total_cmp(&aabbs[b].min.x));
+	let mut active: Vec<usize> = Vec::new();
+	let mut pairs = Vec::new();
+	for &i in &order {
+		let a = &aabbs[i];
+		active.retain(|&j| aabbs[j].max.x >= a.min.x);
+		for &j in &active {
+			if a.intersects(&aabbs[j]) {
+				pairs.push((i.min(j), i.max(j)));
+			}
+		}
+		active.push(i);
+	}
+	pairs.sort_unstable();
+	pairs
+}
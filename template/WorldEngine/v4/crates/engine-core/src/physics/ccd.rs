@@ -0,0 +1,113 @@
+use crate::math::{Quat, Vec3};
+use super::{narrow, AABB, Collider, ColliderType, Obb};
+
+const MAX_SAMPLES: usize = 64;
+const BISECT_ITER: usize = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Toi {
+	pub t: f32,
+	pub normal: Vec3,
+	pub point: Vec3,
+}
+
+pub fn toi_sphere_sphere(
+	pa: &Vec3, ra: f32, da: &Vec3,
+	pb: &Vec3, rb: f32, db: &Vec3
+) -> Option<Toi> {
+	let rel = da.sub(db);
+	let p = pa.sub(pb);
+	let r = ra + rb;
+	let a = rel.len_sq();
+	let b = 2.0 * p.dot(&rel);
+	let c = p.len_sq() - r * r;
+	if c < 0.0 || a < 1e-12 {
+		return None;
+	}
+	let disc = b * b - 4.0 * a * c;
+	if disc < 0.0 {
+		return None;
+	}
+	let t = (-b - disc.sqrt()) / (2.0 * a);
+	if !(0.0..=1.0).contains(&t) {
+		return None;
+	}
+	let normal = p.add(&rel.mul(t)).nrm();
+	let point = pb.add(&db.mul(t)).add(&normal.mul(rb));
+	Some(Toi { t, normal, point })
+}
+
+pub fn toi_sphere_aabb(c: &Vec3, r: f32, d: &Vec3, b: &AABB) -> Option<Toi> {
+	let (t, normal) = b.fatten(r).ray_hit(c, d, 1.0)?;
+	let point = c.add(&d.mul(t)).sub(&normal.mul(r));
+	Some(Toi { t, normal, point })
+}
+
+pub fn toi_sphere_obb(c: &Vec3, r: f32, d: &Vec3, b: &Obb) -> Option<Toi> {
+	let inv = b.rot.conj();
+	let lc = b.to_local(c);
+	let ld = inv.mul_vec3(d);
+	let local = AABB::new(b.half.mul(-1.0), b.half);
+	let (t, n) = local.fatten(r).ray_hit(&lc, &ld, 1.0)?;
+	let normal = b.rot.mul_vec3(&n);
+	let point = c.add(&d.mul(t)).sub(&normal.mul(r));
+	Some(Toi { t, normal, point })
+}
+
+pub fn toi_aabb_aabb(a: &AABB, da: &Vec3, b: &AABB, db: &Vec3) -> Option<Toi> {
+	let half = a.size().mul(0.5);
+	let grown = AABB::new(b.min.sub(&half), b.max.add(&half));
+	let rel = da.sub(db);
+	let (t, normal) = grown.ray_hit(&a.center(), &rel, 1.0)?;
+	let point = a.center().add(&da.mul(t)).sub(&Vec3::new(normal.x * half.x, normal.y * half.y, normal.z * half.z));
+	Some(Toi { t, normal, point })
+}
+
+pub fn toi_sampled(
+	c: &Vec3, r: f32, d: &Vec3,
+	b: &Collider, pos_b: &Vec3, rot_b: &Quat
+) -> Option<Toi> {
+	let len = d.len();
+	if len < 1e-6 || r <= 0.0 {
+		return None;
+	}
+	let probe = Collider::sphere(r);
+	let rot = Quat::identity();
+	let hit = |t: f32| narrow::collide(&probe, &c.add(&d.mul(t)), &rot, b, pos_b, rot_b);
+	if hit(0.0).is_some() {
+		return None;
+	}
+	let n = ((len / r).ceil() as usize).clamp(1, MAX_SAMPLES);
+	let mut lo = 0.0;
+	for i in 1..=n {
+		let mut hi = i as f32 / n as f32;
+		let Some(mut col) = hit(hi) else {
+			lo = hi;
+			continue;
+		};
+		for _ in 0..BISECT_ITER {
+			let mid = (lo + hi) * 0.5;
+			match hit(mid) {
+				Some(c) => {
+					hi = mid;
+					col = c;
+				}
+				None => lo = mid,
+			}
+		}
+		return Some(Toi { t: lo, normal: -col.normal, point: col.point });
+	}
+	None
+}
+
+pub fn toi_collider(
+	c: &Vec3, r: f32, d: &Vec3,
+	b: &Collider, pos_b: &Vec3, rot_b: &Quat, db: &Vec3
+) -> Option<Toi> {
+	let rel = d.sub(db);
+	match b.typ {
+		ColliderType::Sphere => toi_sphere_sphere(c, r, d, &b.center(pos_b, rot_b), b.radius, db),
+		ColliderType::Box => toi_sphere_obb(c, r, &rel, &b.obb(pos_b, rot_b)),
+		ColliderType::Capsule | ColliderType::ConvexHull => toi_sampled(c, r, &rel, b, pos_b, rot_b),
+	}
+}
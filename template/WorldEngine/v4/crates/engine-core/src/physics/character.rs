@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+use crate::math::{det, Quat, Vec3};
+use super::{
+	layer_bit, narrow, touches_unloaded, voxel_contacts,
+	AABB, Collider, PhysicsWorld, Unloaded, Voxels, MASK_ALL,
+};
+
+const MAX_ITER: usize = 8;
+const MIN_DEPTH: f32 = 1e-4;
+const GROUND_EPS: f32 = 0.05;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CharConfig {
+	pub radius: f32,
+	pub height: f32,
+	pub step_h: f32,
+	pub max_slope: f32,
+	pub snap: f32,
+	pub push: f32,
+	pub mask: u32,
+}
+
+impl Default for CharConfig {
+	fn default() -> Self {
+		Self {
+			radius: 0.3,
+			height: 1.8,
+			step_h: 1.05,
+			max_slope: 50f32.to_radians(),
+			snap: 0.3,
+			push: 1.0,
+			mask: MASK_ALL,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Hit {
+	n: Vec3,
+	id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharController {
+	pub cfg: CharConfig,
+	pub pos: Vec3,
+	pub vel: Vec3,
+	pub body: Option<u64>,
+	pub grounded: bool,
+	pub ground_n: Vec3,
+}
+
+struct Env<'a> {
+	world: &'a PhysicsWorld,
+	vox: Option<&'a dyn Voxels>,
+	col: Collider,
+	rot: Quat,
+	up: Vec3,
+	min_up: f32,
+	mask: u32,
+	skip: Option<u64>,
+}
+
+impl Env<'_> {
+	fn walkable(&self, n: &Vec3) -> bool {
+		n.dot(&self.up) >= self.min_up
+	}
+
+	fn contacts(&self, pos: &Vec3, out: &mut Vec<(Vec3, f32, Option<u64>)>) {
+		out.clear();
+		let bb = self.col.aabb_rot(pos, &self.rot);
+		self.world.tree().query(&bb, |id| {
+			if Some(id) == self.skip {
+				return true;
+			}
+			let Some(body) = self.world.get(id) else {
+				return true;
+			};
+			let Some(col) = body.col.as_ref().filter(|c| !c.is_trg && self.mask & layer_bit(c.layer) != 0) else {
+				return true;
+			};
+			if let Some(c) = narrow::collide(&self.col, pos, &self.rot, col, &body.pos, &body.rot) {
+				out.push((c.normal.mul(-1.0), c.depth, Some(id)));
+			}
+			true
+		});
+		if let Some(v) = self.vox {
+			for c in voxel_contacts(v, &self.col, pos, &self.rot, self.world.cfg.unloaded) {
+				out.push((c.normal.mul(-1.0), c.depth, None));
+			}
+		}
+	}
+
+	// moves in sub-steps no longer than half the radius, pushing out of anything overlapped along the way;
+	// walkable surfaces lift straight up so slopes are climbed instead of pushing back
+	fn slide(&self, pos: &Vec3, disp: &Vec3, flat_steep: bool, hits: &mut Vec<Hit>) -> Vec3 {
+		let n = ((disp.len() / (self.col.radius * 0.5)).ceil() as usize).max(1);
+		let step = disp.mul(1.0 / n as f32);
+		let mut p = *pos;
+		let mut cs = Vec::new();
+		for _ in 0..n {
+			p = p.add(&step);
+			for _ in 0..MAX_ITER {
+				self.contacts(&p, &mut cs);
+				cs.retain(|c| c.1 > MIN_DEPTH);
+				hits.extend(cs.iter().map(|&(n, _, id)| Hit { n, id }));
+				// only the deepest is resolved per pass, coplanar voxel faces would otherwise add up
+				let Some(&(n, depth, _)) = cs.iter().max_by(|a, b| a.1.total_cmp(&b.1)) else {
+					break;
+				};
+				let mut push = n;
+				if self.walkable(&n) {
+					push = self.up;
+				} else if flat_steep {
+					let flat = n.sub(&self.up.mul(n.dot(&self.up)));
+					if flat.len_sq() > 1e-6 {
+						push = flat.nrm();
+					}
+				}
+				let along = push.dot(&n).max(0.1);
+				p = p.add(&push.mul(depth / along));
+			}
+		}
+		p
+	}
+
+	// terrain and bodies that won't be shoved aside are stepped over
+	fn fixed(&self, h: &Hit) -> bool {
+		h.id.and_then(|id| self.world.get(id)).is_none_or(|b| !b.is_dynamic())
+	}
+
+	fn ground(&self, hits: &[Hit]) -> Option<Vec3> {
+		hits.iter()
+			.map(|h| h.n)
+			.filter(|n| self.walkable(n))
+			.max_by(|a, b| a.dot(&self.up).total_cmp(&b.dot(&self.up)))
+	}
+}
+
+pub(crate) fn up_rot(up: &Vec3) -> Quat {
+	let axis = Vec3::UP.cross(up);
+	let s = axis.len();
+	if s < 1e-6 {
+		return if up.dot(&Vec3::UP) >= 0.0 { Quat::identity() } else { Quat::from_axis_angle(&Vec3::RIGHT, std::f32::consts::PI) };
+	}
+	Quat::from_axis_angle(&axis.mul(1.0 / s), det::atan2(s, up.dot(&Vec3::UP)))
+}
+
+fn horiz(v: &Vec3, up: &Vec3) -> Vec3 {
+	v.sub(&up.mul(v.dot(up)))
+}
+
+impl CharController {
+	pub fn new(pos: Vec3, radius: f32, height: f32) -> Self {
+		Self {
+			cfg: CharConfig {
+				radius,
+				height,
+				..Default::default()
+			},
+			pos,
+			vel: Vec3::ZERO,
+			body: None,
+			grounded: false,
+			ground_n: Vec3::UP,
+		}
+	}
+
+	pub fn with_cfg(mut self, cfg: CharConfig) -> Self {
+		self.cfg = cfg;
+		self
+	}
+
+	pub fn with_body(mut self, id: u64) -> Self {
+		self.body = Some(id);
+		self
+	}
+
+	pub fn collider(&self) -> Collider {
+		Collider::capsule(self.cfg.radius, self.cfg.height)
+	}
+
+	pub fn feet(&self, up: &Vec3) -> Vec3 {
+		self.pos.sub(&up.mul(self.cfg.height * 0.5))
+	}
+
+	// desired velocity is taken along the ground; an upward component while grounded starts a jump
+	pub fn mv(&mut self, world: &mut PhysicsWorld, vox: Option<&dyn Voxels>, desired: &Vec3, dt: f32) -> Vec3 {
+		if dt <= 0.0 {
+			return Vec3::ZERO;
+		}
+		let g = world.cfg.gravity;
+		let up = if g.len_sq() > 1e-12 { g.mul(-1.0).nrm() } else { Vec3::UP };
+		let env = Env {
+			world,
+			vox,
+			col: self.collider(),
+			rot: up_rot(&up),
+			up,
+			min_up: det::cos(self.cfg.max_slope),
+			mask: self.cfg.mask,
+			skip: self.body,
+		};
+		if let Some(v) = vox {
+			if world.cfg.unloaded == Unloaded::Freeze && touches_unloaded(v, &env.col.aabb_rot(&self.pos, &env.rot)) {
+				self.vel = Vec3::ZERO;
+				return Vec3::ZERO;
+			}
+		}
+		let start = self.pos;
+		let mut hits = Vec::new();
+		let want_up = desired.dot(&up);
+		let mut vy = self.vel.dot(&up);
+		let was_grounded = self.grounded;
+		if was_grounded {
+			vy = want_up.max(0.0);
+		}
+		vy -= g.len() * dt;
+		if was_grounded && vy <= 0.0 {
+			vy = 0.0;
+		}
+		let mut h = horiz(desired, &up);
+		if was_grounded && vy <= 0.0 && h.len_sq() > 0.0 {
+			let on_plane = h.sub(&self.ground_n.mul(h.dot(&self.ground_n)));
+			if on_plane.len_sq() > 1e-12 {
+				h = on_plane.nrm().mul(h.len());
+			}
+		}
+		let hd = h.mul(dt);
+		let mut p = env.slide(&start, &hd, was_grounded, &mut hits);
+		let want = hd.len();
+		let got = |q: &Vec3| horiz(&q.sub(&start), &up).dot(&hd) / want.max(1e-6);
+		let blocked = hits.iter().any(|h| !env.walkable(&h.n) && h.n.dot(&hd) < 0.0 && env.fixed(h));
+		if was_grounded && blocked && want > 1e-6 && got(&p) < 0.9 * want {
+			let mut step_hits = Vec::new();
+			// reach far enough to put the capsule centre over the edge it climbs
+			let reach = hd.mul(want.max(self.cfg.radius + GROUND_EPS) / want);
+			let p1 = env.slide(&start, &up.mul(self.cfg.step_h), false, &mut step_hits);
+			let p2 = env.slide(&p1, &reach, false, &mut step_hits);
+			let rise = p2.sub(&start).dot(&up);
+			step_hits.clear();
+			let p3 = env.slide(&p2, &up.mul(-rise), false, &mut step_hits);
+			if env.ground(&step_hits).is_some() && got(&p3) > got(&p) + 1e-4 {
+				p = p3;
+				hits.extend(step_hits);
+			}
+		}
+		let mut v_hits = Vec::new();
+		p = env.slide(&p, &up.mul(vy * dt), false, &mut v_hits);
+		let mut ground = if vy <= 0.0 { env.ground(&v_hits) } else { None };
+		if v_hits.iter().any(|h| h.n.dot(&up) < -0.5) && vy > 0.0 {
+			vy = 0.0;
+		}
+		if ground.is_none() && vy <= 0.0 {
+			let probe = if was_grounded { self.cfg.snap } else { GROUND_EPS };
+			let mut g_hits = Vec::new();
+			let q = env.slide(&p, &up.mul(-probe), false, &mut g_hits);
+			ground = env.ground(&g_hits);
+			if ground.is_some() {
+				p = q;
+			}
+			v_hits.extend(g_hits);
+		}
+		hits.extend(v_hits);
+		let pushes: Vec<(u64, Vec3)> = hits.iter().filter_map(|h| h.id.map(|id| (id, h.n.mul(-1.0)))).collect();
+		self.grounded = ground.is_some();
+		self.ground_n = ground.unwrap_or(up);
+		if self.grounded {
+			vy = 0.0;
+		}
+		let moved = p.sub(&start);
+		self.pos = p;
+		self.vel = horiz(&moved, &up).mul(1.0 / dt).add(&up.mul(vy));
+		self.push(world, &pushes, &h);
+		// the proxy body trails by one step so the world's own integration lands it on the controller
+		if let Some(body) = self.body.and_then(|id| world.get_mut(id)) {
+			body.pos = start;
+			body.rb.vel = moved.mul(1.0 / dt);
+		}
+		moved
+	}
+
+	fn push(&self, world: &mut PhysicsWorld, pushes: &[(u64, Vec3)], h: &Vec3) {
+		let mut done: Vec<u64> = Vec::new();
+		for &(id, n_in) in pushes {
+			if done.contains(&id) {
+				continue;
+			}
+			let Some(body) = world.get_mut(id).filter(|b| b.is_dynamic()) else {
+				continue;
+			};
+			let into = h.dot(&n_in);
+			if into <= 0.0 {
+				continue;
+			}
+			let rel = into - body.rb.vel.dot(&n_in);
+			if rel > 0.0 {
+				body.rb.add_impulse(n_in.mul(rel * self.cfg.push));
+			}
+			done.push(id);
+		}
+	}
+
+	pub fn aabb(&self, up: &Vec3) -> AABB {
+		self.collider().aabb_rot(&self.pos, &up_rot(up))
+	}
+}
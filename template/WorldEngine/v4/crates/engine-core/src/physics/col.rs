@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use crate::math::Vec3;
+use crate::math::{Quat, Vec3};
+use super::{ConvexHull, PhysMat};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct AABB {
@@ -64,6 +65,82 @@ impl AABB {
 		}
 	}
 
+	pub fn contains_aabb(&self, other: &AABB) -> bool {
+		self.contains(&other.min) && self.contains(&other.max)
+	}
+
+	pub fn fatten(&self, margin: f32) -> Self {
+		let m = Vec3::new(margin, margin, margin);
+		Self {
+			min: self.min.sub(&m),
+			max: self.max.add(&m),
+		}
+	}
+
+	pub fn seg_overlaps(&self, o: &Vec3, d: &Vec3, max_t: f32) -> bool {
+		let (o, d) = ([o.x, o.y, o.z], [d.x, d.y, d.z]);
+		let (lo, hi) = ([self.min.x, self.min.y, self.min.z], [self.max.x, self.max.y, self.max.z]);
+		let mut t0 = 0.0f32;
+		let mut t1 = max_t;
+		for k in 0..3 {
+			if d[k].abs() < 1e-9 {
+				if o[k] < lo[k] || o[k] > hi[k] {
+					return false;
+				}
+				continue;
+			}
+			let inv = 1.0 / d[k];
+			let (ta, tb) = ((lo[k] - o[k]) * inv, (hi[k] - o[k]) * inv);
+			t0 = t0.max(ta.min(tb));
+			t1 = t1.min(ta.max(tb));
+			if t0 > t1 {
+				return false;
+			}
+		}
+		true
+	}
+
+	pub fn ray_hit(&self, o: &Vec3, d: &Vec3, max_t: f32) -> Option<(f32, Vec3)> {
+		let (o, d) = ([o.x, o.y, o.z], [d.x, d.y, d.z]);
+		let (lo, hi) = ([self.min.x, self.min.y, self.min.z], [self.max.x, self.max.y, self.max.z]);
+		let mut t_min = f32::NEG_INFINITY;
+		let mut t_max = f32::INFINITY;
+		let mut axis = 0;
+		let mut sign = 0.0;
+		for k in 0..3 {
+			if d[k].abs() < 1e-9 {
+				if o[k] < lo[k] || o[k] > hi[k] {
+					return None;
+				}
+				continue;
+			}
+			let inv = 1.0 / d[k];
+			let (mut ta, mut tb) = ((lo[k] - o[k]) * inv, (hi[k] - o[k]) * inv);
+			let mut s = -1.0;
+			if ta > tb {
+				std::mem::swap(&mut ta, &mut tb);
+				s = 1.0;
+			}
+			if ta > t_min {
+				t_min = ta;
+				axis = k;
+				sign = s;
+			}
+			t_max = t_max.min(tb);
+		}
+		if t_min > t_max || t_min < 0.0 || t_min > max_t {
+			return None;
+		}
+		let mut n = [0.0; 3];
+		n[axis] = sign;
+		Some((t_min, Vec3::new(n[0], n[1], n[2])))
+	}
+
+	pub fn area(&self) -> f32 {
+		let d = self.size();
+		2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+	}
+
 	pub fn merge(&self, other: &AABB) -> Self {
 		Self {
 			min: Vec3::new(
@@ -80,11 +157,69 @@ impl AABB {
 	}
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Obb {
+	pub center: Vec3,
+	pub rot: Quat,
+	pub half: Vec3,
+}
+
+impl Obb {
+	pub fn new(center: Vec3, rot: Quat, half: Vec3) -> Self {
+		Self { center, rot, half }
+	}
+
+	pub fn axes(&self) -> [Vec3; 3] {
+		[
+			self.rot.mul_vec3(&Vec3::RIGHT),
+			self.rot.mul_vec3(&Vec3::FORWARD),
+			self.rot.mul_vec3(&Vec3::UP),
+		]
+	}
+
+	pub fn half_arr(&self) -> [f32; 3] {
+		[self.half.x, self.half.y, self.half.z]
+	}
+
+	pub fn to_local(&self, p: &Vec3) -> Vec3 {
+		self.rot.conj().mul_vec3(&p.sub(&self.center))
+	}
+
+	pub fn to_world(&self, p: &Vec3) -> Vec3 {
+		self.center.add(&self.rot.mul_vec3(p))
+	}
+
+	pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+		let l = self.to_local(p);
+		self.to_world(&Vec3::new(
+			l.x.clamp(-self.half.x, self.half.x),
+			l.y.clamp(-self.half.y, self.half.y),
+			l.z.clamp(-self.half.z, self.half.z),
+		))
+	}
+
+	pub fn contains(&self, p: &Vec3) -> bool {
+		let l = self.to_local(p);
+		l.x.abs() <= self.half.x && l.y.abs() <= self.half.y && l.z.abs() <= self.half.z
+	}
+
+	pub fn aabb(&self) -> AABB {
+		let ax = self.axes();
+		let ext = Vec3::new(
+			ax[0].x.abs() * self.half.x + ax[1].x.abs() * self.half.y + ax[2].x.abs() * self.half.z,
+			ax[0].y.abs() * self.half.x + ax[1].y.abs() * self.half.y + ax[2].y.abs() * self.half.z,
+			ax[0].z.abs() * self.half.x + ax[1].z.abs() * self.half.y + ax[2].z.abs() * self.half.z,
+		);
+		AABB::new(self.center.sub(&ext), self.center.add(&ext))
+	}
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ColliderType {
 	Box,
 	Sphere,
 	Capsule,
+	ConvexHull,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +230,11 @@ pub struct Collider {
 	pub radius: f32,
 	pub height: f32,
 	pub is_trg: bool,
+	pub hull: Option<ConvexHull>,
+	pub mat: Option<PhysMat>,
+	pub layer: u8,
+	pub group: i32,
+	pub mask: Option<u32>,
 }
 
 impl Default for Collider {
@@ -106,6 +246,11 @@ impl Default for Collider {
 			radius: 0.5,
 			height: 2.0,
 			is_trg: false,
+			hull: None,
+			mat: None,
+			layer: 0,
+			group: 0,
+			mask: None,
 		}
 	}
 }
@@ -136,6 +281,155 @@ impl Collider {
 		}
 	}
 
+	// verts are stored about their centroid so inertia and inner radius hold for any pivot,
+	// the offset puts the shape back where the points were
+	pub fn convex_hull(points: &[Vec3]) -> Option<Self> {
+		let mut hull = ConvexHull::build(points)?;
+		let offset = hull.recentre();
+		Some(Self {
+			typ: ColliderType::ConvexHull,
+			offset,
+			hull: Some(hull),
+			..Default::default()
+		})
+	}
+
+	pub fn with_mat(mut self, mat: PhysMat) -> Self {
+		self.mat = Some(mat);
+		self
+	}
+
+	pub fn with_layer(mut self, layer: u8) -> Self {
+		self.layer = layer;
+		self
+	}
+
+	pub fn with_group(mut self, group: i32) -> Self {
+		self.group = group;
+		self
+	}
+
+	pub fn with_mask(mut self, mask: u32) -> Self {
+		self.mask = Some(mask);
+		self
+	}
+
+	pub fn with_trg(mut self, is_trg: bool) -> Self {
+		self.is_trg = is_trg;
+		self
+	}
+
+	pub fn center(&self, pos: &Vec3, rot: &Quat) -> Vec3 {
+		pos.add(&rot.mul_vec3(&self.offset))
+	}
+
+	pub fn obb(&self, pos: &Vec3, rot: &Quat) -> Obb {
+		Obb::new(self.center(pos, rot), *rot, self.size.mul(0.5))
+	}
+
+	pub fn seg(&self, pos: &Vec3, rot: &Quat) -> (Vec3, Vec3) {
+		let c = self.center(pos, rot);
+		let half = (self.height * 0.5 - self.radius).max(0.0);
+		let axis = rot.mul_vec3(&Vec3::UP).mul(half);
+		(c.sub(&axis), c.add(&axis))
+	}
+
+	pub fn inertia(&self, mass: f32) -> Vec3 {
+		let base = match self.typ {
+			ColliderType::Box => {
+				let s = self.size;
+				Vec3::new(s.y * s.y + s.z * s.z, s.x * s.x + s.z * s.z, s.x * s.x + s.y * s.y).mul(mass / 12.0)
+			}
+			ColliderType::Sphere => {
+				let i = 0.4 * mass * self.radius * self.radius;
+				Vec3::new(i, i, i)
+			}
+			ColliderType::Capsule => {
+				let r = self.radius;
+				let h = (self.height - 2.0 * r).max(0.0);
+				let v_cyl = h;
+				let v_sph = 4.0 / 3.0 * r;
+				let m_cyl = mass * v_cyl / (v_cyl + v_sph).max(1e-9);
+				let m_sph = mass - m_cyl;
+				let iz = m_cyl * r * r * 0.5 + m_sph * 0.4 * r * r;
+				let ix = m_cyl * (h * h / 12.0 + r * r * 0.25) + m_sph * (0.4 * r * r + h * h * 0.25 + 0.375 * h * r);
+				Vec3::new(ix, ix, iz)
+			}
+			ColliderType::ConvexHull => self.hull.as_ref().map_or(Vec3::ZERO, |h| h.inertia(mass)),
+		};
+		let o = self.offset;
+		base.add(&Vec3::new(o.y * o.y + o.z * o.z, o.x * o.x + o.z * o.z, o.x * o.x + o.y * o.y).mul(mass))
+	}
+
+	pub fn volume(&self) -> f32 {
+		let r = self.radius;
+		match self.typ {
+			ColliderType::Box => self.size.x * self.size.y * self.size.z,
+			ColliderType::Sphere => 4.0 / 3.0 * std::f32::consts::PI * r * r * r,
+			ColliderType::Capsule => std::f32::consts::PI * r * r * ((self.height - 2.0 * r).max(0.0) + 4.0 / 3.0 * r),
+			ColliderType::ConvexHull => self.hull.as_ref().map_or(0.0, |h| h.volume_centroid().0),
+		}
+	}
+
+	pub fn inner_radius(&self) -> f32 {
+		match self.typ {
+			ColliderType::Box => self.size.x.min(self.size.y).min(self.size.z) * 0.5,
+			ColliderType::Sphere | ColliderType::Capsule => self.radius,
+			ColliderType::ConvexHull => self.hull.as_ref().map_or(0.0, |h| h.inner_radius()),
+		}
+	}
+
+	pub fn support(&self, pos: &Vec3, rot: &Quat, dir: &Vec3) -> Vec3 {
+		let c = self.center(pos, rot);
+		let d = dir.nrm();
+		match self.typ {
+			ColliderType::Box => {
+				let obb = self.obb(pos, rot);
+				let h = obb.half_arr();
+				obb.axes()
+					.iter()
+					.zip(h)
+					.fold(c, |acc, (ax, h)| acc.add(&ax.mul(if ax.dot(&d) >= 0.0 { h } else { -h })))
+			}
+			ColliderType::Sphere => c.add(&d.mul(self.radius)),
+			ColliderType::Capsule => {
+				let (p0, p1) = self.seg(pos, rot);
+				let p = if p1.sub(&p0).dot(&d) >= 0.0 { p1 } else { p0 };
+				p.add(&d.mul(self.radius))
+			}
+			ColliderType::ConvexHull => match &self.hull {
+				Some(h) => c.add(&rot.mul_vec3(&h.support(&rot.conj().mul_vec3(&d)))),
+				None => c,
+			},
+		}
+	}
+
+	pub fn aabb_rot(&self, pos: &Vec3, rot: &Quat) -> AABB {
+		match self.typ {
+			ColliderType::Box => self.obb(pos, rot).aabb(),
+			ColliderType::Sphere => {
+				let c = self.center(pos, rot);
+				let r = Vec3::new(self.radius, self.radius, self.radius);
+				AABB::new(c.sub(&r), c.add(&r))
+			}
+			ColliderType::Capsule => {
+				let (p0, p1) = self.seg(pos, rot);
+				let r = Vec3::new(self.radius, self.radius, self.radius);
+				AABB::new(p0.sub(&r), p0.add(&r)).merge(&AABB::new(p1.sub(&r), p1.add(&r)))
+			}
+			ColliderType::ConvexHull => {
+				let c = self.center(pos, rot);
+				let Some(h) = &self.hull else {
+					return AABB::new(c, c);
+				};
+				h.verts
+					.iter()
+					.map(|v| c.add(&rot.mul_vec3(v)))
+					.fold(AABB::new(c, c), |acc, v| acc.expand(&v))
+			}
+		}
+	}
+
 	pub fn aabb(&self, pos: &Vec3) -> AABB {
 		let center = pos.add(&self.offset);
 		match self.typ {
@@ -151,6 +445,13 @@ impl Collider {
 					Vec3::new(center.x + self.radius, center.y + self.radius, center.z + half_h),
 				)
 			}
+			ColliderType::ConvexHull => match &self.hull {
+				Some(h) => {
+					let b = h.aabb();
+					AABB::new(b.min.add(&center), b.max.add(&center))
+				}
+				None => AABB::new(center, center),
+			},
 		}
 	}
 }
@@ -162,6 +463,35 @@ pub struct Collision {
 	pub normal: Vec3,
 	pub depth: f32,
 	pub point: Vec3,
+	pub points: Vec<Vec3>,
+}
+
+impl Collision {
+	pub fn new(normal: Vec3, depth: f32, point: Vec3) -> Self {
+		Self {
+			ent_a: 0,
+			ent_b: 0,
+			normal,
+			depth,
+			point,
+			points: vec![point],
+		}
+	}
+
+	pub fn with_points(mut self, points: Vec<Vec3>) -> Self {
+		if !points.is_empty() {
+			let sum = points.iter().fold(Vec3::ZERO, |acc, p| acc.add(p));
+			self.point = sum.mul(1.0 / points.len() as f32);
+			self.points = points;
+		}
+		self
+	}
+
+	pub fn flip(mut self) -> Self {
+		self.normal = -self.normal;
+		std::mem::swap(&mut self.ent_a, &mut self.ent_b);
+		self
+	}
 }
 
 pub fn test_aabb_aabb(a: &AABB, b: &AABB) -> Option<Collision> {
@@ -181,13 +511,7 @@ pub fn test_aabb_aabb(a: &AABB, b: &AABB) -> Option<Collision> {
 		let dir = if a.center().z < b.center().z { -1.0 } else { 1.0 };
 		(Vec3::new(0.0, 0.0, dir), overlap_z)
 	};
-	Some(Collision {
-		ent_a: 0,
-		ent_b: 0,
-		normal,
-		depth,
-		point: a.center().lrp(&b.center(), 0.5),
-	})
+	Some(Collision::new(normal, depth, a.center().lrp(&b.center(), 0.5)))
 }
 
 pub fn test_sphere_sphere(
@@ -204,11 +528,5 @@ pub fn test_sphere_sphere(
 	let normal = if dist > 0.0001 { diff.mul(1.0 / dist) } else { Vec3::UP };
 	let depth = r_sum - dist;
 	let point = pos_a.add(&normal.mul(radius_a));
-	Some(Collision {
-		ent_a: 0,
-		ent_b: 0,
-		normal,
-		depth,
-		point,
-	})
+	Some(Collision::new(normal, depth, point))
 }
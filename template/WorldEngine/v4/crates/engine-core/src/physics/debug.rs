@@ -0,0 +1,223 @@
+use crate::math::{Quat, Vec3};
+use super::{AABB, Collider, ColliderType, Collision, PhysicsWorld, RigidBodyType};
+
+pub const DBG_SHAPES: u32 = 1 << 0;
+pub const DBG_AABBS: u32 = 1 << 1;
+pub const DBG_CONTACTS: u32 = 1 << 2;
+pub const DBG_JOINTS: u32 = 1 << 3;
+pub const DBG_SLEEP: u32 = 1 << 4;
+pub const DBG_TREE: u32 = 1 << 5;
+pub const DBG_ALL: u32 = (1 << 6) - 1;
+
+// floats per vertex: x y z r g b
+pub const DBG_STRIDE: usize = 6;
+
+const SEGS: usize = 16;
+const NORMAL_LEN: f32 = 0.25;
+
+const CLR_STATIC: Vec3 = Vec3 { x: 0.3, y: 0.8, z: 0.3 };
+const CLR_DYNAMIC: Vec3 = Vec3 { x: 0.3, y: 0.6, z: 1.0 };
+const CLR_KINEMATIC: Vec3 = Vec3 { x: 1.0, y: 0.8, z: 0.2 };
+const CLR_TRG: Vec3 = Vec3 { x: 0.9, y: 0.3, z: 0.9 };
+const CLR_ASLEEP: Vec3 = Vec3 { x: 0.45, y: 0.45, z: 0.45 };
+const CLR_AABB: Vec3 = Vec3 { x: 0.9, y: 0.9, z: 0.3 };
+const CLR_POINT: Vec3 = Vec3 { x: 1.0, y: 0.2, z: 0.2 };
+const CLR_NORMAL: Vec3 = Vec3 { x: 1.0, y: 0.6, z: 0.2 };
+const CLR_TERRAIN: Vec3 = Vec3 { x: 0.7, y: 0.5, z: 0.3 };
+const CLR_JOINT: Vec3 = Vec3 { x: 0.2, y: 0.9, z: 0.9 };
+const CLR_BROKEN: Vec3 = Vec3 { x: 0.9, y: 0.1, z: 0.1 };
+const CLR_AWAKE: Vec3 = Vec3 { x: 1.0, y: 1.0, z: 1.0 };
+
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+	pub flags: u32,
+	pub lines: Vec<f32>,
+	pub points: Vec<f32>,
+}
+
+impl Default for DebugDraw {
+	fn default() -> Self {
+		Self::new(DBG_SHAPES | DBG_CONTACTS | DBG_JOINTS)
+	}
+}
+
+impl DebugDraw {
+	pub fn new(flags: u32) -> Self {
+		Self {
+			flags,
+			lines: Vec::new(),
+			points: Vec::new(),
+		}
+	}
+
+	pub fn has(&self, flag: u32) -> bool {
+		self.flags & flag != 0
+	}
+
+	pub fn line_cnt(&self) -> usize {
+		self.lines.len() / (DBG_STRIDE * 2)
+	}
+
+	pub fn point_cnt(&self) -> usize {
+		self.points.len() / DBG_STRIDE
+	}
+
+	pub fn clr(&mut self) {
+		self.lines.clear();
+		self.points.clear();
+	}
+
+	pub fn line(&mut self, a: &Vec3, b: &Vec3, c: &Vec3) {
+		self.lines.extend_from_slice(&[a.x, a.y, a.z, c.x, c.y, c.z, b.x, b.y, b.z, c.x, c.y, c.z]);
+	}
+
+	pub fn point(&mut self, p: &Vec3, c: &Vec3) {
+		self.points.extend_from_slice(&[p.x, p.y, p.z, c.x, c.y, c.z]);
+	}
+
+	pub fn aabb(&mut self, bb: &AABB, c: &Vec3) {
+		let h = bb.size().mul(0.5);
+		self.cube(&bb.center(), &Quat::identity(), &h, c);
+	}
+
+	fn cube(&mut self, center: &Vec3, rot: &Quat, h: &Vec3, c: &Vec3) {
+		let corner = |i: usize| {
+			let l = Vec3::new(
+				if i & 1 != 0 { h.x } else { -h.x },
+				if i & 2 != 0 { h.y } else { -h.y },
+				if i & 4 != 0 { h.z } else { -h.z },
+			);
+			center.add(&rot.mul_vec3(&l))
+		};
+		for i in 0..8 {
+			for bit in [1, 2, 4] {
+				if i & bit == 0 {
+					self.line(&corner(i), &corner(i | bit), c);
+				}
+			}
+		}
+	}
+
+	// circle in the plane of u and v, or just the half that starts at u
+	fn arc(&mut self, center: &Vec3, u: &Vec3, v: &Vec3, r: f32, half: bool, c: &Vec3) {
+		let span = if half { std::f32::consts::PI } else { std::f32::consts::TAU };
+		let at = |i: usize| {
+			let a = span * i as f32 / SEGS as f32;
+			center.add(&u.mul(r * a.cos())).add(&v.mul(r * a.sin()))
+		};
+		for i in 0..SEGS {
+			self.line(&at(i), &at(i + 1), c);
+		}
+	}
+
+	pub fn collider(&mut self, col: &Collider, pos: &Vec3, rot: &Quat, c: &Vec3) {
+		let center = col.center(pos, rot);
+		let (ax, ay, az) = (rot.mul_vec3(&Vec3::RIGHT), rot.mul_vec3(&Vec3::FORWARD), rot.mul_vec3(&Vec3::UP));
+		match col.typ {
+			ColliderType::Box => self.cube(&center, rot, &col.size.mul(0.5), c),
+			ColliderType::Sphere => {
+				self.arc(&center, &ax, &ay, col.radius, false, c);
+				self.arc(&center, &ay, &az, col.radius, false, c);
+				self.arc(&center, &az, &ax, col.radius, false, c);
+			}
+			ColliderType::Capsule => {
+				let (lo, hi) = col.seg(pos, rot);
+				let r = col.radius;
+				self.arc(&lo, &ax, &ay, r, false, c);
+				self.arc(&hi, &ax, &ay, r, false, c);
+				for side in [ax, ay, ax.mul(-1.0), ay.mul(-1.0)] {
+					self.line(&lo.add(&side.mul(r)), &hi.add(&side.mul(r)), c);
+				}
+				self.arc(&hi, &ax, &az, r, true, c);
+				self.arc(&hi, &ay, &az, r, true, c);
+				self.arc(&lo, &ax, &az.mul(-1.0), r, true, c);
+				self.arc(&lo, &ay, &az.mul(-1.0), r, true, c);
+			}
+			ColliderType::ConvexHull => {
+				let Some(hull) = col.hull.as_ref() else {
+					return;
+				};
+				let world = |i: u32| center.add(&rot.mul_vec3(&hull.verts[i as usize]));
+				// each edge shows up once per winding direction, keep only one
+				for f in &hull.faces {
+					for k in 0..3 {
+						let (a, b) = (f[k], f[(k + 1) % 3]);
+						if a < b {
+							self.line(&world(a), &world(b), c);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	fn contact(&mut self, col: &Collision, c: &Vec3) {
+		let pts = if col.points.is_empty() { std::slice::from_ref(&col.point) } else { &col.points[..] };
+		for p in pts {
+			self.point(p, &CLR_POINT);
+			self.line(p, &p.add(&col.normal.mul(NORMAL_LEN + col.depth)), c);
+		}
+	}
+
+	pub fn draw(&mut self, world: &PhysicsWorld) {
+		self.clr();
+		for body in world.bodies() {
+			let asleep = body.rb.is_sleeping;
+			if self.has(DBG_SHAPES) {
+				if let Some(col) = &body.col {
+					let c = if asleep && self.has(DBG_SLEEP) {
+						CLR_ASLEEP
+					} else if col.is_trg {
+						CLR_TRG
+					} else {
+						match body.rb.typ {
+							RigidBodyType::Static => CLR_STATIC,
+							RigidBodyType::Dynamic => CLR_DYNAMIC,
+							RigidBodyType::Kinematic => CLR_KINEMATIC,
+						}
+					};
+					self.collider(col, &body.pos, &body.rot, &c);
+				}
+			}
+			if self.has(DBG_AABBS) {
+				if let Some(bb) = body.aabb() {
+					self.aabb(&bb, &CLR_AABB);
+				}
+			}
+			if self.has(DBG_SLEEP) && body.rb.typ == RigidBodyType::Dynamic {
+				self.point(&body.pos, if asleep { &CLR_ASLEEP } else { &CLR_AWAKE });
+			}
+		}
+		if self.has(DBG_CONTACTS) {
+			for col in world.contacts() {
+				self.contact(col, &CLR_NORMAL);
+			}
+			for col in world.terrain_contacts() {
+				self.contact(col, &CLR_TERRAIN);
+			}
+		}
+		if self.has(DBG_JOINTS) {
+			for j in world.joints() {
+				let (Some(a), Some(b)) = (world.get(j.a), world.get(j.b)) else {
+					continue;
+				};
+				let pa = a.pos.add(&a.rot.mul_vec3(&j.anchor_a));
+				let pb = b.pos.add(&b.rot.mul_vec3(&j.anchor_b));
+				let c = if j.broken || !j.enabled { CLR_BROKEN } else { CLR_JOINT };
+				self.line(&a.pos, &pa, &c);
+				self.line(&pa, &pb, &c);
+				self.line(&pb, &b.pos, &c);
+				self.point(&pa, &c);
+				self.point(&pb, &c);
+			}
+		}
+		if self.has(DBG_TREE) {
+			let root_h = world.tree().height().max(1) as f32;
+			world.tree().each_node(|bb, h| {
+				// leaves dark, root bright
+				let t = 0.25 + 0.75 * h as f32 / root_h;
+				self.aabb(bb, &Vec3::new(t, t * 0.5, 1.0 - t * 0.5));
+			});
+		}
+	}
+}
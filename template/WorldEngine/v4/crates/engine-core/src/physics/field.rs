@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use crate::math::{Perlin, Vec3};
+use super::{layer_bit, AABB, Body, Voxels, MASK_ALL};
+
+const MIN_DIST: f32 = 0.1;
+// per axis samples when a sphere volume measures how much of a body it holds
+const SPHERE_SAMPLES: usize = 4;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FieldShape {
+	Aabb(AABB),
+	Sphere { center: Vec3, radius: f32 },
+	// every voxel cell holding this block id, e.g. water
+	Blocks(u16),
+}
+
+impl FieldShape {
+	pub fn contains(&self, p: &Vec3, vox: Option<&dyn Voxels>) -> bool {
+		match self {
+			Self::Aabb(bb) => bb.contains(p),
+			Self::Sphere { center, radius } => p.sub(center).len_sq() <= radius * radius,
+			Self::Blocks(id) => vox.and_then(|v| v.block(p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32)) == Some(*id),
+		}
+	}
+
+	pub fn bounds(&self) -> Option<AABB> {
+		match self {
+			Self::Aabb(bb) => Some(*bb),
+			Self::Sphere { center, radius } => {
+				let r = Vec3::new(*radius, *radius, *radius);
+				Some(AABB::new(center.sub(&r), center.add(&r)))
+			}
+			Self::Blocks(_) => None,
+		}
+	}
+
+	// volume of bb inside the shape and the middle of that part
+	pub fn overlap(&self, bb: &AABB, vox: Option<&dyn Voxels>) -> (f32, Vec3) {
+		match self {
+			Self::Aabb(f) => box_overlap(bb, f),
+			Self::Sphere { .. } => {
+				let n = SPHERE_SAMPLES;
+				let step = bb.size().mul(1.0 / n as f32);
+				let cell = step.x * step.y * step.z;
+				let (mut vol, mut c) = (0.0, Vec3::ZERO);
+				for z in 0..n {
+					for y in 0..n {
+						for x in 0..n {
+							let p = bb.min.add(&Vec3::new((x as f32 + 0.5) * step.x, (y as f32 + 0.5) * step.y, (z as f32 + 0.5) * step.z));
+							if self.contains(&p, vox) {
+								vol += cell;
+								c = c.add(&p.mul(cell));
+							}
+						}
+					}
+				}
+				(vol, if vol > 0.0 { c.mul(1.0 / vol) } else { Vec3::ZERO })
+			}
+			Self::Blocks(id) => {
+				let Some(v) = vox else {
+					return (0.0, Vec3::ZERO);
+				};
+				let (mut vol, mut c) = (0.0, Vec3::ZERO);
+				let (lo, hi) = (bb.min, bb.max);
+				for z in lo.z.floor() as i32..=hi.z.floor() as i32 {
+					for y in lo.y.floor() as i32..=hi.y.floor() as i32 {
+						for x in lo.x.floor() as i32..=hi.x.floor() as i32 {
+							if v.block(x, y, z) != Some(*id) {
+								continue;
+							}
+							let p = Vec3::new(x as f32, y as f32, z as f32);
+							let (cv, cc) = box_overlap(bb, &AABB::new(p, p.add(&Vec3::ONE)));
+							vol += cv;
+							c = c.add(&cc.mul(cv));
+						}
+					}
+				}
+				(vol, if vol > 0.0 { c.mul(1.0 / vol) } else { Vec3::ZERO })
+			}
+		}
+	}
+}
+
+fn box_overlap(a: &AABB, b: &AABB) -> (f32, Vec3) {
+	let lo = Vec3::new(a.min.x.max(b.min.x), a.min.y.max(b.min.y), a.min.z.max(b.min.z));
+	let hi = Vec3::new(a.max.x.min(b.max.x), a.max.y.min(b.max.y), a.max.z.min(b.max.z));
+	let d = hi.sub(&lo);
+	if d.x <= 0.0 || d.y <= 0.0 || d.z <= 0.0 {
+		return (0.0, Vec3::ZERO);
+	}
+	(d.x * d.y * d.z, lo.lrp(&hi, 0.5))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FieldKind {
+	// lift from displaced fluid against gravity, drag pulls bodies toward the flow
+	Buoyancy { density: f32, drag: f32, ang_drag: f32, flow: Vec3 },
+	// drag toward the wind velocity, perlin noise over space and time adds gusts
+	Wind { vel: Vec3, drag: f32, turbulence: f32, scale: f32, spd: f32 },
+	// inverse square pull in m/s^2 at one metre, negative strength repels
+	Attractor { center: Vec3, strength: f32 },
+	// swirl about the axis with an inward pull and lift along it, all as accelerations
+	Vortex { center: Vec3, axis: Vec3, strength: f32, pull: f32, lift: f32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ForceField {
+	pub id: u64,
+	pub shape: FieldShape,
+	pub kind: FieldKind,
+	pub mask: u32,
+	pub enabled: bool,
+}
+
+impl ForceField {
+	pub fn new(shape: FieldShape, kind: FieldKind) -> Self {
+		Self {
+			id: 0,
+			shape,
+			kind,
+			mask: MASK_ALL,
+			enabled: true,
+		}
+	}
+
+	pub fn with_mask(mut self, mask: u32) -> Self {
+		self.mask = mask;
+		self
+	}
+
+	pub fn buoyancy(shape: FieldShape, density: f32) -> Self {
+		Self::new(shape, FieldKind::Buoyancy { density, drag: 1.0, ang_drag: 1.0, flow: Vec3::ZERO })
+	}
+
+	pub fn wind(shape: FieldShape, vel: Vec3) -> Self {
+		Self::new(shape, FieldKind::Wind { vel, drag: 1.0, turbulence: 0.3, scale: 0.1, spd: 1.0 })
+	}
+
+	pub fn attractor(shape: FieldShape, center: Vec3, strength: f32) -> Self {
+		Self::new(shape, FieldKind::Attractor { center, strength })
+	}
+
+	pub fn vortex(shape: FieldShape, center: Vec3, axis: Vec3, strength: f32) -> Self {
+		Self::new(shape, FieldKind::Vortex { center, axis: axis.nrm(), strength, pull: 0.0, lift: 0.0 })
+	}
+
+	fn hits(&self, body: &Body) -> bool {
+		let layer = body.col.as_ref().map_or(0, |c| c.layer);
+		self.enabled && self.mask & layer_bit(layer) != 0
+	}
+
+	// force and torque about the body position, none when the body is outside
+	pub fn force(&self, body: &Body, gravity: &Vec3, noise: &Perlin, time: f32, vox: Option<&dyn Voxels>) -> Option<(Vec3, Vec3)> {
+		if !self.hits(body) {
+			return None;
+		}
+		let p = body.pos;
+		let m = body.rb.mass;
+		if let FieldKind::Buoyancy { density, drag, ang_drag, flow } = self.kind {
+			let col = body.col.as_ref()?;
+			let bb = body.aabb()?;
+			if self.shape.bounds().is_some_and(|f| !f.intersects(&bb)) {
+				return None;
+			}
+			let (vol, c) = self.shape.overlap(&bb, vox);
+			let full = bb.size();
+			let full = full.x * full.y * full.z;
+			if vol <= 0.0 || full <= 0.0 {
+				return None;
+			}
+			let frac = (vol / full).min(1.0);
+			// lift acts at the middle of the submerged part, so uneven immersion turns the body
+			let lift = gravity.mul(-density * col.volume() * frac);
+			let pull = flow.sub(&body.rb.vel).mul(drag * frac * m);
+			let torque = c.sub(&p).cross(&lift).sub(&body.rb.ang_vel.mul(ang_drag * frac * m));
+			return Some((lift.add(&pull), torque));
+		}
+		if !self.shape.contains(&p, vox) {
+			return None;
+		}
+		let f = match self.kind {
+			FieldKind::Wind { vel, drag, turbulence, scale, spd } => {
+				let q = p.mul(scale);
+				let t = time * spd;
+				// three decorrelated samples make a gust vector around the mean wind
+				let gust = Vec3::new(
+					noise.noise3d(q.x + t, q.y, q.z),
+					noise.noise3d(q.x + 31.7, q.y + t, q.z),
+					noise.noise3d(q.x, q.y + 57.3, q.z + t),
+				);
+				let w = vel.add(&gust.mul(vel.len() * turbulence));
+				w.sub(&body.rb.vel).mul(drag)
+			}
+			FieldKind::Attractor { center, strength } => {
+				let d = center.sub(&p);
+				let r = d.len().max(MIN_DIST);
+				d.mul(strength * m / (r * r * r))
+			}
+			FieldKind::Vortex { center, axis, strength, pull, lift } => {
+				let d = p.sub(&center);
+				let radial = d.sub(&axis.mul(d.dot(&axis)));
+				let r = radial.len();
+				if r < MIN_DIST {
+					axis.mul(lift * m)
+				} else {
+					let out = radial.mul(1.0 / r);
+					axis.cross(&out).mul(strength).sub(&out.mul(pull)).add(&axis.mul(lift)).mul(m)
+				}
+			}
+			FieldKind::Buoyancy { .. } => Vec3::ZERO,
+		};
+		Some((f, Vec3::ZERO))
+	}
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use crate::math::Vec3;
+use crate::spatial::SpatialHash;
+use super::{sweep_aabb, AABB, Unloaded, Voxels};
+
+const EPS: f32 = 1e-6;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FluidConfig {
+	pub radius: f32,
+	pub density: f32,
+	pub viscosity: f32,
+	pub surface_tension: f32,
+	pub relax: f32,
+	pub iterations: u32,
+	pub max_particles: usize,
+	pub gravity: Vec3,
+	pub bounds: Option<AABB>,
+	pub unloaded: Unloaded,
+}
+
+impl Default for FluidConfig {
+	fn default() -> Self {
+		Self {
+			radius: 0.1,
+			density: 1000.0,
+			viscosity: 0.01,
+			surface_tension: 0.001,
+			relax: 100.0,
+			iterations: 3,
+			max_particles: 10000,
+			gravity: Vec3::new(0.0, 0.0, -9.81),
+			bounds: None,
+			unloaded: Unloaded::Freeze,
+		}
+	}
+}
+
+// position based fluid: each iteration moves particles to pull their density back to rest
+#[derive(Debug, Clone, Default)]
+pub struct Fluid {
+	pub cfg: FluidConfig,
+	pub pos: Vec<Vec3>,
+	pub vel: Vec<Vec3>,
+	pub rho: Vec<f32>,
+	pred: Vec<Vec3>,
+	lambda: Vec<f32>,
+	nbr: Vec<u32>,
+	nbr_at: Vec<u32>,
+	hash: SpatialHash,
+}
+
+struct Kernel {
+	h: f32,
+	poly6: f32,
+	spiky: f32,
+	w_dq: f32,
+}
+
+impl Kernel {
+	fn new(h: f32) -> Self {
+		let poly6 = 315.0 / (64.0 * PI * h.powi(9));
+		let dq = 0.2 * h;
+		Self {
+			h,
+			poly6,
+			spiky: -45.0 / (PI * h.powi(6)),
+			w_dq: poly6 * (h * h - dq * dq).powi(3),
+		}
+	}
+
+	fn w(&self, r2: f32) -> f32 {
+		let d = self.h * self.h - r2;
+		if d <= 0.0 { 0.0 } else { self.poly6 * d * d * d }
+	}
+
+	fn grad(&self, r: &Vec3) -> Vec3 {
+		let len = r.len();
+		if len < EPS || len >= self.h {
+			return Vec3::ZERO;
+		}
+		let d = self.h - len;
+		r.mul(self.spiky * d * d / len)
+	}
+}
+
+impl Fluid {
+	pub fn new(cfg: FluidConfig) -> Self {
+		Self {
+			cfg,
+			..Default::default()
+		}
+	}
+
+	// smoothing radius, four particle radii like the ts sim
+	pub fn h(&self) -> f32 {
+		self.cfg.radius * 4.0
+	}
+
+	// particles sit a diameter apart at rest, mass follows from that
+	pub fn mass(&self) -> f32 {
+		let d = self.cfg.radius * 2.0;
+		self.cfg.density * d * d * d
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.pos.len()
+	}
+
+	pub fn clr(&mut self) {
+		self.pos.clear();
+		self.vel.clear();
+		self.rho.clear();
+	}
+
+	pub fn add(&mut self, pos: Vec3, vel: Vec3) -> Option<usize> {
+		if self.pos.len() >= self.cfg.max_particles {
+			return None;
+		}
+		self.pos.push(pos);
+		self.vel.push(vel);
+		self.rho.push(self.cfg.density);
+		Some(self.pos.len() - 1)
+	}
+
+	// packs the box at rest spacing, returns how many fit under the particle cap
+	pub fn fill(&mut self, bb: &AABB, vel: Vec3) -> usize {
+		let d = self.cfg.radius * 2.0;
+		let n = bb.size().mul(1.0 / d);
+		let (nx, ny, nz) = (n.x.floor().max(1.0) as i32, n.y.floor().max(1.0) as i32, n.z.floor().max(1.0) as i32);
+		let mut added = 0;
+		for z in 0..nz {
+			for y in 0..ny {
+				for x in 0..nx {
+					let p = bb.min.add(&Vec3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5).mul(d));
+					if self.add(p, vel).is_none() {
+						return added;
+					}
+					added += 1;
+				}
+			}
+		}
+		added
+	}
+
+	pub fn del_outside(&mut self, bb: &AABB) {
+		let mut i = 0;
+		while i < self.pos.len() {
+			if bb.contains(&self.pos[i]) {
+				i += 1;
+				continue;
+			}
+			self.pos.swap_remove(i);
+			self.vel.swap_remove(i);
+			self.rho.swap_remove(i);
+		}
+	}
+
+	pub fn positions(&self) -> Vec<f32> {
+		self.pos.iter().flat_map(|p| [p.x, p.y, p.z]).collect()
+	}
+
+	pub fn velocities(&self) -> Vec<f32> {
+		self.vel.iter().flat_map(|v| [v.x, v.y, v.z]).collect()
+	}
+
+	fn neighbours(&mut self, k: &Kernel) {
+		self.hash.spacing = k.h;
+		self.hash.build(&self.pred);
+		self.nbr.clear();
+		self.nbr_at.clear();
+		let h2 = k.h * k.h;
+		for (i, p) in self.pred.iter().enumerate() {
+			self.nbr_at.push(self.nbr.len() as u32);
+			let nbr = &mut self.nbr;
+			let pred = &self.pred;
+			self.hash.query(p, k.h, |j| {
+				if j != i && pred[j].sub(p).len_sq() < h2 {
+					nbr.push(j as u32);
+				}
+			});
+		}
+		self.nbr_at.push(self.nbr.len() as u32);
+	}
+
+	fn nbrs(&self, i: usize) -> &[u32] {
+		&self.nbr[self.nbr_at[i] as usize..self.nbr_at[i + 1] as usize]
+	}
+
+	// keeps a particle inside the bounds and out of solid voxels, swept from where it started the step
+	fn confine(&self, i: usize, p: &Vec3, vox: Option<&dyn Voxels>) -> Vec3 {
+		let r = self.cfg.radius;
+		let mut p = *p;
+		if let Some(bb) = &self.cfg.bounds {
+			// not clamp, bounds thinner than a particle would panic there
+			p = Vec3::new(
+				p.x.max(bb.min.x + r).min(bb.max.x - r),
+				p.y.max(bb.min.y + r).min(bb.max.y - r),
+				p.z.max(bb.min.z + r).min(bb.max.z - r),
+			);
+		}
+		if let Some(v) = vox {
+			let from = self.pos[i];
+			let half = Vec3::new(r, r, r);
+			let (moved, _) = sweep_aabb(v, &AABB::new(from.sub(&half), from.add(&half)), &p.sub(&from), self.cfg.unloaded);
+			p = from.add(&moved);
+		}
+		p
+	}
+
+	pub fn step(&mut self, vox: Option<&dyn Voxels>, dt: f32) {
+		let n = self.pos.len();
+		if dt <= 0.0 || n == 0 {
+			return;
+		}
+		let k = Kernel::new(self.h());
+		let g = self.cfg.gravity;
+		self.pred.clear();
+		for i in 0..n {
+			self.vel[i] = self.vel[i].add(&g.mul(dt));
+			let p = self.pos[i].add(&self.vel[i].mul(dt));
+			let p = self.confine(i, &p, vox);
+			self.pred.push(p);
+		}
+		self.neighbours(&k);
+		let vol = self.mass() / self.cfg.density;
+		self.lambda.resize(n, 0.0);
+		let mut delta = vec![Vec3::ZERO; n];
+		for _ in 0..self.cfg.iterations.max(1) {
+			for i in 0..n {
+				let pi = self.pred[i];
+				let mut rho = k.w(0.0);
+				let mut gi = Vec3::ZERO;
+				let mut sum = 0.0;
+				for &j in self.nbrs(i) {
+					let r = pi.sub(&self.pred[j as usize]);
+					rho += k.w(r.len_sq());
+					let gj = k.grad(&r).mul(vol);
+					gi = gi.add(&gj);
+					sum += gj.len_sq();
+				}
+				let rho = rho * self.mass();
+				self.rho[i] = rho;
+				// only compression is corrected so the free surface doesn't clump
+				let c = (rho / self.cfg.density - 1.0).max(0.0);
+				self.lambda[i] = -c / (sum + gi.len_sq() + self.cfg.relax);
+			}
+			for (i, d) in delta.iter_mut().enumerate() {
+				let pi = self.pred[i];
+				let mut acc = Vec3::ZERO;
+				for &j in self.nbrs(i) {
+					let j = j as usize;
+					let r = pi.sub(&self.pred[j]);
+					// artificial pressure pulls sparse neighbours together as surface tension
+					let s = k.w(r.len_sq()) / k.w_dq;
+					let corr = -self.cfg.surface_tension * s * s * s * s;
+					acc = acc.add(&k.grad(&r).mul(self.lambda[i] + self.lambda[j] + corr));
+				}
+				*d = acc.mul(vol);
+			}
+			for (i, d) in delta.iter().enumerate() {
+				let p = self.pred[i].add(d);
+				self.pred[i] = self.confine(i, &p, vox);
+			}
+		}
+		let inv = 1.0 / dt;
+		for i in 0..n {
+			self.vel[i] = self.pred[i].sub(&self.pos[i]).mul(inv);
+		}
+		// xsph viscosity blends each velocity toward its neighbourhood
+		let c = self.cfg.viscosity;
+		if c > 0.0 {
+			let mut out = self.vel.clone();
+			for (i, o) in out.iter_mut().enumerate() {
+				let mut acc = Vec3::ZERO;
+				for &j in self.nbrs(i) {
+					let j = j as usize;
+					let w = k.w(self.pred[i].sub(&self.pred[j]).len_sq()) * vol;
+					acc = acc.add(&self.vel[j].sub(&self.vel[i]).mul(w));
+				}
+				*o = o.add(&acc.mul(c));
+			}
+			self.vel = out;
+		}
+		std::mem::swap(&mut self.pos, &mut self.pred);
+	}
+}
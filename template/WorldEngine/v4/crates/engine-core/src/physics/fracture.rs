@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use crate::math::{det, Rng, Vec3};
+use super::{Body, Collider, ColliderType, ConvexHull};
+
+const EPS: f32 = 1e-5;
+const SITE_TRIES: u32 = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FractureConfig {
+	pub min_fragments: u32,
+	pub max_fragments: u32,
+	// summed contact impulse in one step that breaks the body
+	pub threshold: f32,
+	// share of sites packed around the impact point
+	pub cluster: f32,
+	// how many more times fragments can break in turn
+	pub depth: u32,
+	pub seed: u64,
+}
+
+impl Default for FractureConfig {
+	fn default() -> Self {
+		Self {
+			min_fragments: 3,
+			max_fragments: 12,
+			threshold: 100.0,
+			cluster: 0.33,
+			depth: 0,
+			seed: 0,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fracture {
+	pub id: u64,
+	pub point: Vec3,
+	pub impulse: f32,
+	pub frags: Vec<u64>,
+}
+
+// collider as a convex polytope of planar faces in body space, or none for round shapes
+fn polytope(col: &Collider) -> Option<Vec<Vec<Vec3>>> {
+	let o = col.offset;
+	match col.typ {
+		ColliderType::Box => {
+			let h = col.size.mul(0.5);
+			let c = |x: f32, y: f32, z: f32| o.add(&Vec3::new(x * h.x, y * h.y, z * h.z));
+			Some(vec![
+				vec![c(-1.0, -1.0, -1.0), c(-1.0, 1.0, -1.0), c(1.0, 1.0, -1.0), c(1.0, -1.0, -1.0)],
+				vec![c(-1.0, -1.0, 1.0), c(1.0, -1.0, 1.0), c(1.0, 1.0, 1.0), c(-1.0, 1.0, 1.0)],
+				vec![c(-1.0, -1.0, -1.0), c(1.0, -1.0, -1.0), c(1.0, -1.0, 1.0), c(-1.0, -1.0, 1.0)],
+				vec![c(-1.0, 1.0, -1.0), c(-1.0, 1.0, 1.0), c(1.0, 1.0, 1.0), c(1.0, 1.0, -1.0)],
+				vec![c(-1.0, -1.0, -1.0), c(-1.0, -1.0, 1.0), c(-1.0, 1.0, 1.0), c(-1.0, 1.0, -1.0)],
+				vec![c(1.0, -1.0, -1.0), c(1.0, 1.0, -1.0), c(1.0, 1.0, 1.0), c(1.0, -1.0, 1.0)],
+			])
+		}
+		ColliderType::ConvexHull => {
+			let hull = col.hull.as_ref()?;
+			Some(hull.faces.iter().map(|f| f.iter().map(|&i| o.add(&hull.verts[i as usize])).collect()).collect())
+		}
+		_ => None,
+	}
+}
+
+fn centroid(pts: &[Vec3]) -> Vec3 {
+	pts.iter().fold(Vec3::ZERO, |acc, p| acc.add(p)).mul(1.0 / pts.len().max(1) as f32)
+}
+
+fn inside(faces: &[Vec<Vec3>], mid: &Vec3, p: &Vec3) -> bool {
+	faces.iter().all(|f| {
+		let n = f[1].sub(&f[0]).cross(&f[2].sub(&f[0]));
+		// winding varies by source, so orient each plane away from the middle
+		let s = if n.dot(&mid.sub(&f[0])) > 0.0 { -1.0 } else { 1.0 };
+		n.dot(&p.sub(&f[0])) * s <= 0.0
+	})
+}
+
+// keeps the part of the polytope with n.p <= d and closes the cut with a cap face
+fn clip(faces: &[Vec<Vec3>], n: &Vec3, d: f32) -> Vec<Vec<Vec3>> {
+	let mut out = Vec::with_capacity(faces.len() + 1);
+	let mut cap: Vec<Vec3> = Vec::new();
+	for f in faces {
+		let mut poly = Vec::with_capacity(f.len() + 1);
+		for k in 0..f.len() {
+			let (a, b) = (f[k], f[(k + 1) % f.len()]);
+			let (da, db) = (n.dot(&a) - d, n.dot(&b) - d);
+			if da <= EPS {
+				poly.push(a);
+				if da >= -EPS {
+					cap.push(a);
+				}
+			}
+			if (da < -EPS && db > EPS) || (da > EPS && db < -EPS) {
+				let p = a.lrp(&b, da / (da - db));
+				poly.push(p);
+				cap.push(p);
+			}
+		}
+		if poly.len() >= 3 {
+			out.push(poly);
+		}
+	}
+	cap.dedup_by(|a, b| a.sub(b).len_sq() < EPS * EPS);
+	if cap.len() >= 3 {
+		// wind the cap around its middle so later cuts see a proper polygon
+		let c = centroid(&cap);
+		let u = cap[0].sub(&c).nrm();
+		let v = n.cross(&u);
+		cap.sort_by(|a, b| {
+			let (pa, pb) = (a.sub(&c), b.sub(&c));
+			det::atan2(pa.dot(&v), pa.dot(&u)).total_cmp(&det::atan2(pb.dot(&v), pb.dot(&u)))
+		});
+		cap.dedup_by(|a, b| a.sub(b).len_sq() < EPS * EPS);
+		if cap.len() >= 3 {
+			out.push(cap);
+		}
+	}
+	out
+}
+
+// one cell per site: the polytope cut by the bisector plane against every other site
+pub fn voronoi_cells(faces: &[Vec<Vec3>], sites: &[Vec3]) -> Vec<Vec<Vec3>> {
+	let mut cells = Vec::with_capacity(sites.len());
+	for (i, si) in sites.iter().enumerate() {
+		let mut cell = faces.to_vec();
+		for (j, sj) in sites.iter().enumerate() {
+			if i == j || cell.is_empty() {
+				continue;
+			}
+			let n = sj.sub(si).nrm();
+			let d = n.dot(&si.add(sj).mul(0.5));
+			// skip cuts that miss the cell entirely
+			if cell.iter().flatten().all(|p| n.dot(p) <= d + EPS) {
+				continue;
+			}
+			cell = clip(&cell, &n, d);
+		}
+		let verts: Vec<Vec3> = cell.into_iter().flatten().collect();
+		if verts.len() >= 4 {
+			cells.push(verts);
+		}
+	}
+	cells
+}
+
+fn sites(faces: &[Vec<Vec3>], cfg: &FractureConfig, rng: &mut Rng, impact: &Vec3) -> Vec<Vec3> {
+	let pts: Vec<Vec3> = faces.iter().flatten().copied().collect();
+	let mid = centroid(&pts);
+	let (mut lo, mut hi) = (pts[0], pts[0]);
+	for p in &pts {
+		lo = Vec3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z));
+		hi = Vec3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z));
+	}
+	let size = hi.sub(&lo);
+	let lo_n = cfg.min_fragments.max(2);
+	let cnt = rng.range_usize(lo_n as usize, cfg.max_fragments.max(lo_n) as usize + 1);
+	let mut out = Vec::with_capacity(cnt);
+	for k in 0..cnt {
+		let near = (k as f32) < cfg.cluster * cnt as f32;
+		for _ in 0..SITE_TRIES {
+			let p = if near {
+				impact.add(&rng.in_sphere(size.len() * 0.15))
+			} else {
+				Vec3::new(rng.range_f32(lo.x, hi.x), rng.range_f32(lo.y, hi.y), rng.range_f32(lo.z, hi.z))
+			};
+			if inside(faces, &mid, &p) && out.iter().all(|q: &Vec3| q.sub(&p).len_sq() > EPS) {
+				out.push(p);
+				break;
+			}
+		}
+	}
+	out
+}
+
+// splits a box or hull body into fragment bodies numbered from first, mass shared by volume
+pub fn shatter(body: &Body, cfg: &FractureConfig, point: &Vec3, first: u64) -> Vec<Body> {
+	let Some(col) = body.col.as_ref() else {
+		return Vec::new();
+	};
+	let Some(faces) = polytope(col) else {
+		return Vec::new();
+	};
+	let mut rng = Rng::new(cfg.seed ^ body.id);
+	let impact = body.rot.conj().mul_vec3(&point.sub(&body.pos));
+	let sites = sites(&faces, cfg, &mut rng, &impact);
+	if sites.len() < 2 {
+		return Vec::new();
+	}
+	let pieces: Vec<(ConvexHull, f32, Vec3)> = voronoi_cells(&faces, &sites)
+		.into_iter()
+		.filter_map(|verts| {
+			let mut hull = ConvexHull::build(&verts)?;
+			let vol = hull.volume_centroid().0;
+			let c = hull.recentre();
+			(vol > EPS).then_some((hull, vol, c))
+		})
+		.collect();
+	let total: f32 = pieces.iter().map(|p| p.1).sum();
+	if pieces.len() < 2 || total <= 0.0 {
+		return Vec::new();
+	}
+	let (v, w) = (body.rb.vel, body.rb.ang_vel);
+	pieces
+		.into_iter()
+		.enumerate()
+		.map(|(i, (hull, vol, c))| {
+			let r = body.rot.mul_vec3(&c);
+			let mut rb = body.rb.clone();
+			rb.mass = body.rb.mass * vol / total;
+			rb.vel = v.add(&w.cross(&r));
+			rb.force = Vec3::ZERO;
+			rb.torque = Vec3::ZERO;
+			rb.wake();
+			let frag = Collider {
+				typ: ColliderType::ConvexHull,
+				offset: Vec3::ZERO,
+				hull: Some(hull),
+				..col.clone()
+			};
+			Body::new(first + i as u64, body.pos.add(&r), rb).with_col(frag).with_rot(body.rot)
+		})
+		.collect()
+}
@@ -0,0 +1,267 @@
+use crate::math::{Quat, Vec3};
+use super::{Collider, Collision};
+
+const GJK_ITER: usize = 64;
+const EPA_ITER: usize = 64;
+const EPA_TOL: f32 = 1e-4;
+const EPS: f32 = 1e-10;
+
+pub trait Support {
+	fn support(&self, dir: &Vec3) -> Vec3;
+}
+
+pub struct Posed<'a> {
+	pub col: &'a Collider,
+	pub pos: Vec3,
+	pub rot: Quat,
+}
+
+impl<'a> Posed<'a> {
+	pub fn new(col: &'a Collider, pos: Vec3, rot: Quat) -> Self {
+		Self { col, pos, rot }
+	}
+}
+
+impl Support for Posed<'_> {
+	fn support(&self, dir: &Vec3) -> Vec3 {
+		self.col.support(&self.pos, &self.rot, dir)
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Vert {
+	p: Vec3,
+	a: Vec3,
+	b: Vec3,
+}
+
+fn sup<A: Support, B: Support>(a: &A, b: &B, d: &Vec3) -> Vert {
+	let pa = a.support(d);
+	let pb = b.support(&-*d);
+	Vert { p: pa.sub(&pb), a: pa, b: pb }
+}
+
+fn same_dir(a: &Vec3, b: &Vec3) -> bool {
+	a.dot(b) > 0.0
+}
+
+fn perp(v: &Vec3) -> Vec3 {
+	let axis = if v.x.abs() < 0.57 { Vec3::RIGHT } else { Vec3::FORWARD };
+	v.cross(&axis)
+}
+
+fn triple(a: &Vec3, b: &Vec3) -> Vec3 {
+	let d = a.cross(b).cross(a);
+	if d.len_sq() > EPS { d } else { perp(a) }
+}
+
+struct Simplex {
+	pts: [Vert; 4],
+	n: usize,
+}
+
+impl Simplex {
+	fn push_front(&mut self, v: Vert) {
+		self.pts = [v, self.pts[0], self.pts[1], self.pts[2]];
+		self.n = (self.n + 1).min(4);
+	}
+
+	fn set(&mut self, vs: &[Vert]) {
+		self.pts[..vs.len()].copy_from_slice(vs);
+		self.n = vs.len();
+	}
+
+	fn line(&mut self, d: &mut Vec3) -> bool {
+		let [a, b, ..] = self.pts;
+		let ab = b.p.sub(&a.p);
+		let ao = -a.p;
+		if same_dir(&ab, &ao) {
+			*d = triple(&ab, &ao);
+		} else {
+			self.set(&[a]);
+			*d = ao;
+		}
+		false
+	}
+
+	fn tri(&mut self, d: &mut Vec3) -> bool {
+		let [a, b, c, _] = self.pts;
+		let ab = b.p.sub(&a.p);
+		let ac = c.p.sub(&a.p);
+		let ao = -a.p;
+		let abc = ab.cross(&ac);
+		if same_dir(&abc.cross(&ac), &ao) {
+			if same_dir(&ac, &ao) {
+				self.set(&[a, c]);
+				*d = triple(&ac, &ao);
+				return false;
+			}
+			self.set(&[a, b]);
+			return self.line(d);
+		}
+		if same_dir(&ab.cross(&abc), &ao) {
+			self.set(&[a, b]);
+			return self.line(d);
+		}
+		if same_dir(&abc, &ao) {
+			*d = abc;
+		} else {
+			self.set(&[a, c, b]);
+			*d = -abc;
+		}
+		false
+	}
+
+	fn tet(&mut self, d: &mut Vec3) -> bool {
+		let [a, b, c, e] = self.pts;
+		let ab = b.p.sub(&a.p);
+		let ac = c.p.sub(&a.p);
+		let ae = e.p.sub(&a.p);
+		let ao = -a.p;
+		if same_dir(&ab.cross(&ac), &ao) {
+			self.set(&[a, b, c]);
+			return self.tri(d);
+		}
+		if same_dir(&ac.cross(&ae), &ao) {
+			self.set(&[a, c, e]);
+			return self.tri(d);
+		}
+		if same_dir(&ae.cross(&ab), &ao) {
+			self.set(&[a, e, b]);
+			return self.tri(d);
+		}
+		true
+	}
+
+	fn next(&mut self, d: &mut Vec3) -> bool {
+		match self.n {
+			2 => self.line(d),
+			3 => self.tri(d),
+			4 => self.tet(d),
+			_ => false,
+		}
+	}
+}
+
+fn run_gjk<A: Support, B: Support>(a: &A, b: &B) -> Option<[Vert; 4]> {
+	let first = sup(a, b, &Vec3::RIGHT);
+	let mut s = Simplex { pts: [first; 4], n: 1 };
+	let mut d = -first.p;
+	if d.len_sq() < EPS {
+		d = Vec3::UP;
+	}
+	for _ in 0..GJK_ITER {
+		let v = sup(a, b, &d);
+		if v.p.dot(&d) <= 0.0 {
+			return None;
+		}
+		s.push_front(v);
+		if s.next(&mut d) {
+			return Some(s.pts);
+		}
+		if d.len_sq() < EPS {
+			return None;
+		}
+	}
+	None
+}
+
+pub fn gjk<A: Support, B: Support>(a: &A, b: &B) -> bool {
+	run_gjk(a, b).is_some()
+}
+
+#[derive(Clone, Copy)]
+struct Face {
+	v: [usize; 3],
+	n: Vec3,
+	d: f32,
+}
+
+fn mk_face(pts: &[Vert], v: [usize; 3]) -> Option<Face> {
+	let (a, b, c) = (pts[v[0]].p, pts[v[1]].p, pts[v[2]].p);
+	let n = b.sub(&a).cross(&c.sub(&a));
+	let l = n.len();
+	if l < 1e-9 {
+		return None;
+	}
+	let n = n.mul(1.0 / l);
+	Some(Face { v, n, d: n.dot(&a) })
+}
+
+fn bary(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3) -> (f32, f32, f32) {
+	let v0 = b.sub(a);
+	let v1 = c.sub(a);
+	let v2 = p.sub(a);
+	let d00 = v0.dot(&v0);
+	let d01 = v0.dot(&v1);
+	let d11 = v1.dot(&v1);
+	let d20 = v2.dot(&v0);
+	let d21 = v2.dot(&v1);
+	let den = d00 * d11 - d01 * d01;
+	if den.abs() < EPS {
+		return (1.0, 0.0, 0.0);
+	}
+	let v = (d11 * d20 - d01 * d21) / den;
+	let w = (d00 * d21 - d01 * d20) / den;
+	(1.0 - v - w, v, w)
+}
+
+fn epa<A: Support, B: Support>(a: &A, b: &B, tet: [Vert; 4]) -> Option<Collision> {
+	let mut pts: Vec<Vert> = tet.to_vec();
+	let mut faces: Vec<Face> = Vec::new();
+	for (v, opp) in [([0, 1, 2], 3), ([0, 3, 1], 2), ([0, 2, 3], 1), ([1, 3, 2], 0)] {
+		let mut f = mk_face(&pts, v)?;
+		if f.n.dot(&pts[opp].p.sub(&pts[v[0]].p)) > 0.0 {
+			f = mk_face(&pts, [v[0], v[2], v[1]])?;
+		}
+		faces.push(f);
+	}
+	let mut best = faces[0];
+	for _ in 0..EPA_ITER {
+		best = *faces
+			.iter()
+			.min_by(|x, y| x.d.partial_cmp(&y.d).unwrap_or(std::cmp::Ordering::Equal))?;
+		let s = sup(a, b, &best.n);
+		if s.p.dot(&best.n) - best.d < EPA_TOL {
+			break;
+		}
+		let mut horizon: Vec<(usize, usize)> = Vec::new();
+		faces.retain(|f| {
+			if f.n.dot(&s.p.sub(&pts[f.v[0]].p)) <= 0.0 {
+				return true;
+			}
+			for k in 0..3 {
+				let e = (f.v[k], f.v[(k + 1) % 3]);
+				if let Some(i) = horizon.iter().position(|&h| h == (e.1, e.0)) {
+					horizon.swap_remove(i);
+				} else {
+					horizon.push(e);
+				}
+			}
+			false
+		});
+		let si = pts.len();
+		pts.push(s);
+		for (e0, e1) in horizon {
+			if let Some(f) = mk_face(&pts, [e0, e1, si]) {
+				faces.push(f);
+			}
+		}
+		if faces.is_empty() {
+			break;
+		}
+	}
+	if best.d <= 0.0 {
+		return None;
+	}
+	let [i, j, k] = best.v;
+	let (u, v, w) = bary(&best.n.mul(best.d), &pts[i].p, &pts[j].p, &pts[k].p);
+	let pa = pts[i].a.mul(u).add(&pts[j].a.mul(v)).add(&pts[k].a.mul(w));
+	let pb = pts[i].b.mul(u).add(&pts[j].b.mul(v)).add(&pts[k].b.mul(w));
+	Some(Collision::new(best.n, best.d, pa.lrp(&pb, 0.5)))
+}
+
+pub fn test_convex<A: Support, B: Support>(a: &A, b: &B) -> Option<Collision> {
+	let tet = run_gjk(a, b)?;
+	epa(a, b, tet)
+}
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use crate::math::Vec3;
+use super::AABB;
+
+#[derive(Debug, Clone)]
+struct HullFace {
+	v: [usize; 3],
+	n: Vec3,
+	d: f32,
+	outside: Vec<usize>,
+	alive: bool,
+}
+
+impl HullFace {
+	fn new(pts: &[Vec3], a: usize, b: usize, c: usize) -> Self {
+		let n = pts[b].sub(&pts[a]).cross(&pts[c].sub(&pts[a])).nrm();
+		Self {
+			v: [a, b, c],
+			n,
+			d: n.dot(&pts[a]),
+			outside: Vec::new(),
+			alive: true,
+		}
+	}
+
+	fn dist(&self, p: &Vec3) -> f32 {
+		self.n.dot(p) - self.d
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConvexHull {
+	pub verts: Vec<Vec3>,
+	pub faces: Vec<[u32; 3]>,
+}
+
+impl ConvexHull {
+	pub fn build(points: &[Vec3]) -> Option<Self> {
+		if points.len() < 4 {
+			return None;
+		}
+		let mut lo = points[0];
+		let mut hi = points[0];
+		for p in points {
+			lo = Vec3::new(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z));
+			hi = Vec3::new(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z));
+		}
+		let eps = hi.sub(&lo).len() * 1e-5;
+		if eps <= 0.0 {
+			return None;
+		}
+		let (i0, i1) = Self::extreme_pair(points);
+		let line = points[i1].sub(&points[i0]);
+		let i2 = Self::farthest(points, |p| p.sub(&points[i0]).cross(&line).len_sq())?;
+		let plane_n = line.cross(&points[i2].sub(&points[i0])).nrm();
+		let i3 = Self::farthest(points, |p| p.sub(&points[i0]).dot(&plane_n).abs())?;
+		if points[i3].sub(&points[i0]).dot(&plane_n).abs() <= eps {
+			return None;
+		}
+		let mut faces: Vec<HullFace> = Vec::new();
+		let tet = [i0, i1, i2, i3];
+		let centroid = tet.iter().fold(Vec3::ZERO, |acc, &i| acc.add(&points[i])).mul(0.25);
+		for &(a, b, c) in &[(0, 1, 2), (0, 3, 1), (0, 2, 3), (1, 3, 2)] {
+			let mut f = HullFace::new(points, tet[a], tet[b], tet[c]);
+			if f.dist(&centroid) > 0.0 {
+				f = HullFace::new(points, tet[a], tet[c], tet[b]);
+			}
+			faces.push(f);
+		}
+		for (i, p) in points.iter().enumerate() {
+			if tet.contains(&i) {
+				continue;
+			}
+			Self::assign(&mut faces, 0, i, p, eps);
+		}
+		while let Some(fi) = faces.iter().position(|f| f.alive && !f.outside.is_empty()) {
+			let eye = *faces[fi].outside
+				.iter()
+				.max_by(|&&a, &&b| {
+					let da = faces[fi].dist(&points[a]);
+					let db = faces[fi].dist(&points[b]);
+					da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+				})?;
+			let ep = points[eye];
+			let mut horizon: Vec<(usize, usize)> = Vec::new();
+			let mut orphans: Vec<usize> = Vec::new();
+			for f in faces.iter_mut() {
+				if !f.alive || f.dist(&ep) <= eps {
+					continue;
+				}
+				f.alive = false;
+				orphans.append(&mut f.outside);
+				for k in 0..3 {
+					let e = (f.v[k], f.v[(k + 1) % 3]);
+					if let Some(pos) = horizon.iter().position(|&h| h == (e.1, e.0)) {
+						horizon.swap_remove(pos);
+					} else {
+						horizon.push(e);
+					}
+				}
+			}
+			let first_new = faces.len();
+			for (a, b) in horizon {
+				faces.push(HullFace::new(points, a, b, eye));
+			}
+			for i in orphans {
+				if i == eye {
+					continue;
+				}
+				Self::assign(&mut faces, first_new, i, &points[i], eps);
+			}
+		}
+		let mut remap = vec![u32::MAX; points.len()];
+		let mut verts = Vec::new();
+		let mut out_faces = Vec::new();
+		for f in faces.iter().filter(|f| f.alive) {
+			let mut tri = [0u32; 3];
+			for (t, &v) in tri.iter_mut().zip(&f.v) {
+				if remap[v] == u32::MAX {
+					remap[v] = verts.len() as u32;
+					verts.push(points[v]);
+				}
+				*t = remap[v];
+			}
+			out_faces.push(tri);
+		}
+		Some(Self { verts, faces: out_faces })
+	}
+
+	fn extreme_pair(points: &[Vec3]) -> (usize, usize) {
+		let mut ext = [0usize; 6];
+		for (i, p) in points.iter().enumerate() {
+			if p.x < points[ext[0]].x { ext[0] = i; }
+			if p.x > points[ext[1]].x { ext[1] = i; }
+			if p.y < points[ext[2]].y { ext[2] = i; }
+			if p.y > points[ext[3]].y { ext[3] = i; }
+			if p.z < points[ext[4]].z { ext[4] = i; }
+			if p.z > points[ext[5]].z { ext[5] = i; }
+		}
+		let mut best = (ext[0], ext[1]);
+		let mut best_d = -1.0;
+		for i in 0..6 {
+			for j in (i + 1)..6 {
+				let d = points[ext[i]].sub(&points[ext[j]]).len_sq();
+				if d > best_d {
+					best_d = d;
+					best = (ext[i], ext[j]);
+				}
+			}
+		}
+		best
+	}
+
+	fn farthest<F: Fn(&Vec3) -> f32>(points: &[Vec3], score: F) -> Option<usize> {
+		let mut best = None;
+		let mut best_s = 0.0;
+		for (i, p) in points.iter().enumerate() {
+			let s = score(p);
+			if s > best_s {
+				best_s = s;
+				best = Some(i);
+			}
+		}
+		best
+	}
+
+	fn assign(faces: &mut [HullFace], from: usize, i: usize, p: &Vec3, eps: f32) {
+		let mut best: Option<usize> = None;
+		let mut best_d = eps;
+		for (fi, f) in faces.iter().enumerate().skip(from) {
+			if !f.alive {
+				continue;
+			}
+			let d = f.dist(p);
+			if d > best_d {
+				best_d = d;
+				best = Some(fi);
+			}
+		}
+		if let Some(fi) = best {
+			faces[fi].outside.push(i);
+		}
+	}
+
+	pub fn support(&self, dir: &Vec3) -> Vec3 {
+		let mut best = Vec3::ZERO;
+		let mut best_d = f32::MIN;
+		for v in &self.verts {
+			let d = v.dot(dir);
+			if d > best_d {
+				best_d = d;
+				best = *v;
+			}
+		}
+		best
+	}
+
+	pub fn aabb(&self) -> AABB {
+		let Some(first) = self.verts.first() else {
+			return AABB::new(Vec3::ZERO, Vec3::ZERO);
+		};
+		self.verts.iter().fold(AABB::new(*first, *first), |acc, v| acc.expand(v))
+	}
+
+	pub fn ray(&self, o: &Vec3, d: &Vec3, max_t: f32) -> Option<(f32, Vec3)> {
+		let mut t_in = 0.0;
+		let mut t_out = max_t;
+		let mut n_in = None;
+		for f in &self.faces {
+			let a = self.verts[f[0] as usize];
+			let n = self.verts[f[1] as usize].sub(&a).cross(&self.verts[f[2] as usize].sub(&a)).nrm();
+			let dist = n.dot(&a) - n.dot(o);
+			let den = n.dot(d);
+			if den.abs() < 1e-9 {
+				if dist < 0.0 {
+					return None;
+				}
+				continue;
+			}
+			let t = dist / den;
+			if den < 0.0 {
+				if t > t_in {
+					t_in = t;
+					n_in = Some(n);
+				}
+			} else {
+				t_out = t_out.min(t);
+			}
+			if t_in > t_out {
+				return None;
+			}
+		}
+		n_in.map(|n| (t_in, n))
+	}
+
+	pub fn inner_radius(&self) -> f32 {
+		self.faces
+			.iter()
+			.map(|f| {
+				let a = self.verts[f[0] as usize];
+				let n = self.verts[f[1] as usize].sub(&a).cross(&self.verts[f[2] as usize].sub(&a)).nrm();
+				n.dot(&a)
+			})
+			.fold(f32::MAX, f32::min)
+			.max(0.0)
+	}
+
+	pub fn inertia(&self, mass: f32) -> Vec3 {
+		let mut vol = 0.0;
+		let mut sq = Vec3::ZERO;
+		for f in &self.faces {
+			let a = self.verts[f[0] as usize];
+			let b = self.verts[f[1] as usize];
+			let c = self.verts[f[2] as usize];
+			let det = a.dot(&b.cross(&c));
+			vol += det / 6.0;
+			let s = |x: f32, y: f32, z: f32| x * x + y * y + z * z + x * y + y * z + x * z;
+			sq = sq.add(&Vec3::new(s(a.x, b.x, c.x), s(a.y, b.y, c.y), s(a.z, b.z, c.z)).mul(det / 60.0));
+		}
+		if vol.abs() < 1e-9 {
+			return Vec3::ZERO;
+		}
+		let k = mass / vol;
+		Vec3::new((sq.y + sq.z) * k, (sq.x + sq.z) * k, (sq.x + sq.y) * k)
+	}
+
+	// moves the verts so the centroid sits at the origin and returns how far they moved
+	pub fn recentre(&mut self) -> Vec3 {
+		let (_, c) = self.volume_centroid();
+		for v in &mut self.verts {
+			*v = v.sub(&c);
+		}
+		c
+	}
+
+	pub fn volume_centroid(&self) -> (f32, Vec3) {
+		let mut vol = 0.0;
+		let mut c = Vec3::ZERO;
+		for f in &self.faces {
+			let a = self.verts[f[0] as usize];
+			let b = self.verts[f[1] as usize];
+			let d = self.verts[f[2] as usize];
+			let v = a.dot(&b.cross(&d)) / 6.0;
+			vol += v;
+			c = c.add(&a.add(&b).add(&d).mul(v * 0.25));
+		}
+		if vol.abs() > 1e-9 {
+			(vol, c.mul(1.0 / vol))
+		} else {
+			(0.0, Vec3::ZERO)
+		}
+	}
+}
@@ -0,0 +1,36 @@
+pub struct Islands {
+	parent: Vec<usize>,
+	rank: Vec<u8>,
+}
+
+impl Islands {
+	pub fn new(n: usize) -> Self {
+		Self {
+			parent: (0..n).collect(),
+			rank: vec![0; n],
+		}
+	}
+
+	pub fn find(&mut self, mut i: usize) -> usize {
+		while self.parent[i] != i {
+			self.parent[i] = self.parent[self.parent[i]];
+			i = self.parent[i];
+		}
+		i
+	}
+
+	pub fn union(&mut self, a: usize, b: usize) {
+		let (ra, rb) = (self.find(a), self.find(b));
+		if ra == rb {
+			return;
+		}
+		match self.rank[ra].cmp(&self.rank[rb]) {
+			std::cmp::Ordering::Less => self.parent[ra] = rb,
+			std::cmp::Ordering::Greater => self.parent[rb] = ra,
+			std::cmp::Ordering::Equal => {
+				self.parent[rb] = ra;
+				self.rank[ra] += 1;
+			}
+		}
+	}
+}
@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+use crate::math::{det, Quat, Vec3};
+use super::{Body, PhysicsConfig};
+
+const MAX_ROWS: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Motor {
+	pub spd: f32,
+	pub max: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum JointKind {
+	Dist { min: f32, max: f32 },
+	Ball,
+	Hinge { axis_a: Vec3, axis_b: Vec3, lim: Option<(f32, f32)>, motor: Option<Motor> },
+	Slider { axis: Vec3, lim: Option<(f32, f32)>, motor: Option<Motor> },
+	Fixed,
+	Spring { rest: f32, stiffness: f32, damping: f32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Joint {
+	pub id: u64,
+	pub a: u64,
+	pub b: u64,
+	pub anchor_a: Vec3,
+	pub anchor_b: Vec3,
+	pub kind: JointKind,
+	pub enabled: bool,
+	pub collide: bool,
+	pub break_force: f32,
+	pub break_torque: f32,
+	pub broken: bool,
+	pub(crate) rel: Quat,
+}
+
+impl Joint {
+	fn new(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3, kind: JointKind) -> Self {
+		Self {
+			id: 0,
+			a,
+			b,
+			anchor_a,
+			anchor_b,
+			kind,
+			enabled: true,
+			collide: false,
+			break_force: f32::INFINITY,
+			break_torque: f32::INFINITY,
+			broken: false,
+			rel: Quat::identity(),
+		}
+	}
+
+	pub fn dist(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3, len: f32) -> Self {
+		Self::new(a, b, anchor_a, anchor_b, JointKind::Dist { min: len, max: len })
+	}
+
+	pub fn ball(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3) -> Self {
+		Self::new(a, b, anchor_a, anchor_b, JointKind::Ball)
+	}
+
+	pub fn hinge(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3, axis_a: Vec3, axis_b: Vec3) -> Self {
+		let kind = JointKind::Hinge { axis_a: axis_a.nrm(), axis_b: axis_b.nrm(), lim: None, motor: None };
+		Self::new(a, b, anchor_a, anchor_b, kind)
+	}
+
+	pub fn slider(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3, axis: Vec3) -> Self {
+		Self::new(a, b, anchor_a, anchor_b, JointKind::Slider { axis: axis.nrm(), lim: None, motor: None })
+	}
+
+	pub fn fixed(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3) -> Self {
+		Self::new(a, b, anchor_a, anchor_b, JointKind::Fixed)
+	}
+
+	pub fn spring(a: u64, b: u64, anchor_a: Vec3, anchor_b: Vec3, rest: f32, stiffness: f32, damping: f32) -> Self {
+		Self::new(a, b, anchor_a, anchor_b, JointKind::Spring { rest, stiffness, damping })
+	}
+
+	pub fn with_range(mut self, lo: f32, hi: f32) -> Self {
+		self.set_range(lo, hi);
+		self
+	}
+
+	pub fn with_motor(mut self, spd: f32, max: f32) -> Self {
+		self.set_motor(Some(Motor { spd, max }));
+		self
+	}
+
+	pub fn with_break(mut self, force: f32, torque: f32) -> Self {
+		self.break_force = force;
+		self.break_torque = torque;
+		self
+	}
+
+	pub fn with_collide(mut self, collide: bool) -> Self {
+		self.collide = collide;
+		self
+	}
+
+	pub fn set_range(&mut self, lo: f32, hi: f32) {
+		match &mut self.kind {
+			JointKind::Dist { min, max } => {
+				*min = lo;
+				*max = hi;
+			}
+			JointKind::Hinge { lim, .. } | JointKind::Slider { lim, .. } => *lim = Some((lo, hi)),
+			_ => {}
+		}
+	}
+
+	pub fn set_motor(&mut self, m: Option<Motor>) {
+		if let JointKind::Hinge { motor, .. } | JointKind::Slider { motor, .. } = &mut self.kind {
+			*motor = m;
+		}
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.enabled && !self.broken
+	}
+
+	pub(crate) fn init(&mut self, a: &Body, b: &Body) {
+		self.rel = a.rot.conj().mul(&b.rot);
+	}
+
+	pub fn angle(&self, a: &Body, b: &Body) -> f32 {
+		let JointKind::Hinge { axis_a, .. } = self.kind else {
+			return 0.0;
+		};
+		let q = rel_err(self, a, b);
+		2.0 * det::atan2(q.x * axis_a.x + q.y * axis_a.y + q.z * axis_a.z, q.w)
+	}
+}
+
+// rotation of b relative to a since the joint was created, in a's frame
+fn rel_err(j: &Joint, a: &Body, b: &Body) -> Quat {
+	let q = a.rot.conj().mul(&b.rot).mul(&j.rel.conj());
+	if q.w < 0.0 { Quat::new(-q.x, -q.y, -q.z, -q.w) } else { q }
+}
+
+fn perp(n: &Vec3) -> [Vec3; 2] {
+	let axis = if n.x.abs() < 0.57 { Vec3::RIGHT } else { Vec3::FORWARD };
+	let t0 = n.cross(&axis).nrm();
+	[t0, n.cross(&t0)]
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Row {
+	slot: usize,
+	lin: Vec3,
+	ang_a: Vec3,
+	ang_b: Vec3,
+	mass: f32,
+	bias: f32,
+	gamma: f32,
+	lo: f32,
+	hi: f32,
+	imp: f32,
+}
+
+struct JointRows {
+	joint: usize,
+	a: usize,
+	b: usize,
+	inv_a: f32,
+	inv_b: f32,
+	rows: Vec<Row>,
+}
+
+impl JointRows {
+	fn push(&mut self, bodies: &[Body], slot: usize, lin: Vec3, ang_a: Vec3, ang_b: Vec3) -> &mut Row {
+		let (a, b) = (&bodies[self.a], &bodies[self.b]);
+		let k = (self.inv_a + self.inv_b) * lin.len_sq()
+			+ ang_a.dot(&a.rb.mul_inv_inertia(&a.rot, &ang_a))
+			+ ang_b.dot(&b.rb.mul_inv_inertia(&b.rot, &ang_b));
+		self.rows.push(Row {
+			slot,
+			lin,
+			ang_a,
+			ang_b,
+			mass: if k > 0.0 { 1.0 / k } else { 0.0 },
+			bias: 0.0,
+			gamma: 0.0,
+			lo: f32::NEG_INFINITY,
+			hi: f32::INFINITY,
+			imp: 0.0,
+		});
+		self.rows.last_mut().unwrap()
+	}
+
+	fn point(&mut self, bodies: &[Body], slot: usize, dir: Vec3, ra: &Vec3, rb: &Vec3, bias: f32) -> &mut Row {
+		let row = self.push(bodies, slot, dir, ra.cross(&dir), rb.cross(&dir));
+		row.bias = bias;
+		row
+	}
+
+	fn angular(&mut self, bodies: &[Body], slot: usize, axis: Vec3, bias: f32) -> &mut Row {
+		let row = self.push(bodies, slot, Vec3::ZERO, axis, axis);
+		row.bias = bias;
+		row
+	}
+
+	fn cdot(&self, bodies: &[Body], row: &Row) -> f32 {
+		let (a, b) = (&bodies[self.a].rb, &bodies[self.b].rb);
+		row.lin.dot(&b.vel.sub(&a.vel)) + row.ang_b.dot(&b.ang_vel) - row.ang_a.dot(&a.ang_vel)
+	}
+
+	fn apply(&self, bodies: &mut [Body], row: &Row, imp: f32) {
+		let a = &mut bodies[self.a];
+		a.rb.vel = a.rb.vel.sub(&row.lin.mul(imp * self.inv_a));
+		a.rb.ang_vel = a.rb.ang_vel.sub(&a.rb.mul_inv_inertia(&a.rot, &row.ang_a.mul(imp)));
+		let b = &mut bodies[self.b];
+		b.rb.vel = b.rb.vel.add(&row.lin.mul(imp * self.inv_b));
+		b.rb.ang_vel = b.rb.ang_vel.add(&b.rb.mul_inv_inertia(&b.rot, &row.ang_b.mul(imp)));
+	}
+}
+
+#[derive(Default)]
+pub(crate) struct JointSolver {
+	sets: Vec<JointRows>,
+	cache: HashMap<u64, [f32; MAX_ROWS]>,
+}
+
+impl JointSolver {
+	pub fn prepare(
+		&mut self,
+		bodies: &[Body], idx: &HashMap<u64, usize>,
+		joints: &[Joint], cfg: &PhysicsConfig, dt: f32
+	) {
+		self.sets.clear();
+		let beta = cfg.baumgarte / dt;
+		for (ji, j) in joints.iter().enumerate() {
+			if !j.is_active() {
+				continue;
+			}
+			let (Some(&a), Some(&b)) = (idx.get(&j.a), idx.get(&j.b)) else {
+				continue;
+			};
+			if !bodies[a].is_awake() && !bodies[b].is_awake() {
+				continue;
+			}
+			let (inv_a, inv_b) = (bodies[a].rb.inv_mass(), bodies[b].rb.inv_mass());
+			if inv_a + inv_b <= 0.0 {
+				continue;
+			}
+			let mut set = JointRows { joint: ji, a, b, inv_a, inv_b, rows: Vec::with_capacity(6) };
+			Self::build(&mut set, bodies, j, beta, dt);
+			if let Some(old) = self.cache.get(&j.id).filter(|_| cfg.warm_start) {
+				for row in &mut set.rows {
+					row.imp = old[row.slot].clamp(row.lo, row.hi);
+				}
+			}
+			self.sets.push(set);
+		}
+	}
+
+	fn build(set: &mut JointRows, bodies: &[Body], j: &Joint, beta: f32, dt: f32) {
+		let (a, b) = (&bodies[set.a], &bodies[set.b]);
+		let ra = a.rot.mul_vec3(&j.anchor_a);
+		let rb = b.rot.mul_vec3(&j.anchor_b);
+		let d = b.pos.add(&rb).sub(&a.pos.add(&ra));
+		let point3 = |set: &mut JointRows| {
+			for (k, axis) in [Vec3::RIGHT, Vec3::FORWARD, Vec3::UP].into_iter().enumerate() {
+				set.point(bodies, k, axis, &ra, &rb, beta * d.dot(&axis));
+			}
+		};
+		let lock3 = |set: &mut JointRows| {
+			let q = rel_err(j, a, b);
+			let err = a.rot.mul_vec3(&Vec3::new(q.x, q.y, q.z).mul(2.0));
+			for (k, axis) in [Vec3::RIGHT, Vec3::FORWARD, Vec3::UP].into_iter().enumerate() {
+				set.angular(bodies, 3 + k, axis, beta * err.dot(&axis));
+			}
+		};
+		match j.kind {
+			JointKind::Ball => point3(set),
+			JointKind::Fixed => {
+				point3(set);
+				lock3(set);
+			}
+			JointKind::Dist { min, max } => {
+				let len = d.len();
+				if len < 1e-6 {
+					return;
+				}
+				let n = d.mul(1.0 / len);
+				if max - min < 1e-6 {
+					set.point(bodies, 0, n, &ra, &rb, beta * (len - max));
+				} else if len >= max {
+					set.point(bodies, 0, n, &ra, &rb, beta * (len - max)).hi = 0.0;
+				} else if len <= min {
+					set.point(bodies, 0, n, &ra, &rb, beta * (len - min)).lo = 0.0;
+				}
+			}
+			JointKind::Spring { rest, stiffness, damping } => {
+				let len = d.len();
+				if len < 1e-6 {
+					return;
+				}
+				let n = d.mul(1.0 / len);
+				let soft = dt * (damping + dt * stiffness);
+				if soft <= 0.0 {
+					return;
+				}
+				let gamma = 1.0 / soft;
+				let row = set.point(bodies, 0, n, &ra, &rb, 0.0);
+				row.mass = 1.0 / (1.0 / row.mass + gamma);
+				row.gamma = gamma;
+				row.bias = (len - rest) * dt * stiffness * gamma;
+			}
+			JointKind::Hinge { axis_a, axis_b, lim, motor } => {
+				point3(set);
+				let wa = a.rot.mul_vec3(&axis_a);
+				let wb = b.rot.mul_vec3(&axis_b);
+				let err = wa.cross(&wb);
+				for (k, t) in perp(&wa).into_iter().enumerate() {
+					set.angular(bodies, 3 + k, t, beta * err.dot(&t));
+				}
+				if let Some((lo, hi)) = lim {
+					let ang = j.angle(a, b);
+					if ang >= hi {
+						set.angular(bodies, 5, wa, beta * (ang - hi)).hi = 0.0;
+					} else if ang <= lo {
+						set.angular(bodies, 5, wa, beta * (ang - lo)).lo = 0.0;
+					}
+				}
+				if let Some(m) = motor {
+					let row = set.angular(bodies, 6, wa, -m.spd);
+					row.lo = -m.max * dt;
+					row.hi = m.max * dt;
+				}
+			}
+			JointKind::Slider { axis, lim, motor } => {
+				let wa = a.rot.mul_vec3(&axis);
+				// lever from a's origin to b's anchor so the slide offset is felt as torque
+				let ra = d.add(&ra);
+				for (k, t) in perp(&wa).into_iter().enumerate() {
+					set.point(bodies, k, t, &ra, &rb, beta * d.dot(&t));
+				}
+				lock3(set);
+				let off = d.dot(&wa);
+				if let Some((lo, hi)) = lim {
+					if off >= hi {
+						set.point(bodies, 2, wa, &ra, &rb, beta * (off - hi)).hi = 0.0;
+					} else if off <= lo {
+						set.point(bodies, 2, wa, &ra, &rb, beta * (off - lo)).lo = 0.0;
+					}
+				}
+				if let Some(m) = motor {
+					let row = set.point(bodies, 6, wa, &ra, &rb, -m.spd);
+					row.lo = -m.max * dt;
+					row.hi = m.max * dt;
+				}
+			}
+		}
+	}
+
+	pub fn warm(&self, bodies: &mut [Body]) {
+		for set in &self.sets {
+			for row in &set.rows {
+				set.apply(bodies, row, row.imp);
+			}
+		}
+	}
+
+	pub fn solve(&mut self, bodies: &mut [Body]) {
+		for set in &mut self.sets {
+			for i in 0..set.rows.len() {
+				let mut row = set.rows[i];
+				let cdot = set.cdot(bodies, &row);
+				let old = row.imp;
+				row.imp = (old - row.mass * (cdot + row.bias + row.gamma * old)).clamp(row.lo, row.hi);
+				set.apply(bodies, &row, row.imp - old);
+				set.rows[i] = row;
+			}
+		}
+	}
+
+	// marks joints whose applied force or torque exceeded their threshold, returns their indices
+	pub fn store(&mut self, joints: &mut [Joint], dt: f32) -> Vec<usize> {
+		self.cache.clear();
+		let mut broke = Vec::new();
+		for set in self.sets.drain(..) {
+			let j = &mut joints[set.joint];
+			let mut imp = [0.0; MAX_ROWS];
+			let (mut force, mut torque) = (Vec3::ZERO, Vec3::ZERO);
+			for row in &set.rows {
+				imp[row.slot] = row.imp;
+				if row.lin.len_sq() > 0.0 {
+					force = force.add(&row.lin.mul(row.imp));
+				} else {
+					torque = torque.add(&row.ang_b.mul(row.imp));
+				}
+			}
+			if force.len() / dt > j.break_force || torque.len() / dt > j.break_torque {
+				j.broken = true;
+				broke.push(set.joint);
+				continue;
+			}
+			self.cache.insert(j.id, imp);
+		}
+		broke
+	}
+
+	pub fn clr(&mut self) {
+		self.sets.clear();
+		self.cache.clear();
+	}
+}
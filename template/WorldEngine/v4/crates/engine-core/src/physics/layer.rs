@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use super::Collider;
+
+pub const LAYER_CNT: usize = 32;
+
+pub fn layer_bit(layer: u8) -> u32 {
+	1 << (layer as usize % LAYER_CNT)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Layers {
+	names: Vec<String>,
+	matrix: Vec<u32>,
+}
+
+impl Default for Layers {
+	fn default() -> Self {
+		let mut names = vec![String::new(); LAYER_CNT];
+		names[0] = "default".to_string();
+		Self {
+			names,
+			matrix: vec![u32::MAX; LAYER_CNT],
+		}
+	}
+}
+
+impl Layers {
+	pub fn name(&self, layer: u8) -> &str {
+		&self.names[layer as usize % LAYER_CNT]
+	}
+
+	pub fn set_name(&mut self, layer: u8, name: &str) {
+		self.names[layer as usize % LAYER_CNT] = name.to_string();
+	}
+
+	pub fn find(&self, name: &str) -> Option<u8> {
+		if name.is_empty() {
+			return None;
+		}
+		self.names.iter().position(|n| n == name).map(|i| i as u8)
+	}
+
+	pub fn bits(&self, names: &[&str]) -> u32 {
+		names.iter().filter_map(|n| self.find(n)).fold(0, |m, l| m | layer_bit(l))
+	}
+
+	pub fn mask(&self, layer: u8) -> u32 {
+		self.matrix[layer as usize % LAYER_CNT]
+	}
+
+	pub fn set(&mut self, a: u8, b: u8, on: bool) {
+		let (ia, ib) = (a as usize % LAYER_CNT, b as usize % LAYER_CNT);
+		if on {
+			self.matrix[ia] |= layer_bit(b);
+			self.matrix[ib] |= layer_bit(a);
+		} else {
+			self.matrix[ia] &= !layer_bit(b);
+			self.matrix[ib] &= !layer_bit(a);
+		}
+	}
+
+	pub fn collides(&self, a: u8, b: u8) -> bool {
+		self.mask(a) & layer_bit(b) != 0
+	}
+
+	// shared non-zero group wins: positive always collides, negative never does
+	pub fn allows(&self, a: &Collider, b: &Collider) -> bool {
+		if a.group != 0 && a.group == b.group {
+			return a.group > 0;
+		}
+		let mask_a = a.mask.unwrap_or_else(|| self.mask(a.layer));
+		let mask_b = b.mask.unwrap_or_else(|| self.mask(b.layer));
+		mask_a & layer_bit(b.layer) != 0 && mask_b & layer_bit(a.layer) != 0
+	}
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+// ordered by precedence: when two materials disagree the higher rule wins
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CombRule {
+	#[default]
+	Avg,
+	Min,
+	Mul,
+	Max,
+}
+
+impl CombRule {
+	pub fn from_u8(v: u8) -> Self {
+		match v {
+			1 => Self::Min,
+			2 => Self::Mul,
+			3 => Self::Max,
+			_ => Self::Avg,
+		}
+	}
+
+	pub fn apply(&self, a: f32, b: f32) -> f32 {
+		match self {
+			Self::Avg => (a + b) * 0.5,
+			Self::Min => a.min(b),
+			Self::Mul => a * b,
+			Self::Max => a.max(b),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhysMat {
+	pub friction: f32,
+	pub bounce: f32,
+	pub friction_rule: CombRule,
+	pub bounce_rule: CombRule,
+}
+
+impl Default for PhysMat {
+	fn default() -> Self {
+		Self::DEF
+	}
+}
+
+impl PhysMat {
+	pub const DEF: Self = Self::new(0.5, 0.3, CombRule::Avg, CombRule::Avg);
+	pub const ICE: Self = Self::new(0.02, 0.1, CombRule::Min, CombRule::Avg);
+	pub const SAND: Self = Self::new(0.8, 0.05, CombRule::Max, CombRule::Min);
+	pub const RUBBER: Self = Self::new(0.9, 0.9, CombRule::Avg, CombRule::Max);
+	pub const METAL: Self = Self::new(0.4, 0.3, CombRule::Avg, CombRule::Avg);
+	pub const WOOD: Self = Self::new(0.5, 0.4, CombRule::Avg, CombRule::Avg);
+	pub const STONE: Self = Self::new(0.6, 0.2, CombRule::Avg, CombRule::Min);
+
+	pub const fn new(friction: f32, bounce: f32, friction_rule: CombRule, bounce_rule: CombRule) -> Self {
+		Self {
+			friction,
+			bounce,
+			friction_rule,
+			bounce_rule,
+		}
+	}
+
+	pub fn preset(name: &str) -> Option<Self> {
+		match name {
+			"def" => Some(Self::DEF),
+			"ice" => Some(Self::ICE),
+			"sand" => Some(Self::SAND),
+			"rubber" => Some(Self::RUBBER),
+			"metal" => Some(Self::METAL),
+			"wood" => Some(Self::WOOD),
+			"stone" => Some(Self::STONE),
+			_ => None,
+		}
+	}
+
+	pub fn combine_friction(&self, other: &Self) -> f32 {
+		self.friction_rule.max(other.friction_rule).apply(self.friction, other.friction)
+	}
+
+	pub fn combine_bounce(&self, other: &Self) -> f32 {
+		self.bounce_rule.max(other.bounce_rule).apply(self.bounce, other.bounce)
+	}
+}
@@ -1,5 +1,49 @@
 mod col;
+mod hull;
+mod broad;
+mod ccd;
+mod island;
+mod gjk;
 mod rigid;
+mod material;
+mod layer;
+mod solver;
+mod joint;
+mod narrow;
+mod voxel;
+mod world;
+mod query;
+mod character;
+mod debug;
+mod pbd;
+mod fluid;
+mod vehicle;
+mod fracture;
+mod field;
+mod system;
 
-pub use col::{AABB, Collider, ColliderType, Collision};
+pub use col::{AABB, Obb, Collider, ColliderType, Collision, test_aabb_aabb, test_sphere_sphere};
+pub use hull::ConvexHull;
+pub use broad::{AabbTree, sweep_pairs};
+pub use island::Islands;
+pub use ccd::{Toi, toi_sphere_sphere, toi_sphere_aabb, toi_sphere_obb, toi_aabb_aabb, toi_sampled, toi_collider};
+pub use gjk::{gjk, test_convex, Posed, Support};
+pub use material::{CombRule, PhysMat};
+pub use layer::{LAYER_CNT, Layers, layer_bit};
+pub use joint::{Joint, JointKind, Motor};
 pub use rigid::{RigidBody, RigidBodyType};
+pub use narrow::{
+	collide, closest_on_seg, closest_seg_seg,
+	test_sphere_box, test_sphere_capsule, test_capsule_capsule, test_capsule_box, test_box_box,
+};
+pub use voxel::{TERRAIN_ID, Unloaded, Voxels, voxel_contacts, sweep_aabb, touches_unloaded, voxel_ray};
+pub use world::{Body, PhysicsConfig, PhysicsEvent, PhysicsWorld};
+pub use query::{MASK_ALL, RayHit, ray_sphere, ray_capsule, ray_collider};
+pub use character::{CharConfig, CharController};
+pub use debug::{DBG_ALL, DBG_AABBS, DBG_CONTACTS, DBG_JOINTS, DBG_SHAPES, DBG_SLEEP, DBG_STRIDE, DBG_TREE, DebugDraw};
+pub use pbd::{Particle, Pin, SoftBody, SoftCon, SoftConfig, stiffness_compliance};
+pub use fluid::{Fluid, FluidConfig};
+pub use fracture::{Fracture, FractureConfig, shatter, voronoi_cells};
+pub use field::{FieldKind, FieldShape, ForceField};
+pub use vehicle::{TireCurve, Vehicle, VehicleConfig, Wheel, WheelConfig};
+pub use system::{PhysicsInterp, PhysicsSystem};
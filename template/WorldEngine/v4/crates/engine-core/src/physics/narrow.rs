@@ -0,0 +1,464 @@
+use crate::math::{det, Quat, Vec3};
+use super::col::test_sphere_sphere;
+use super::gjk::{test_convex, Posed};
+use super::{Collider, ColliderType, Collision, Obb};
+
+const EPS: f32 = 1e-6;
+const SEG_BOX_ITER: usize = 32;
+const EDGE_BIAS: f32 = 0.95;
+// how far below the deepest vertex a face still counts as the touching feature
+const FEATURE_TOL: f32 = 0.02;
+
+pub fn closest_on_seg(p: &Vec3, a: &Vec3, b: &Vec3) -> Vec3 {
+	let ab = b.sub(a);
+	let l2 = ab.len_sq();
+	if l2 < EPS {
+		return *a;
+	}
+	let t = (p.sub(a).dot(&ab) / l2).clamp(0.0, 1.0);
+	a.add(&ab.mul(t))
+}
+
+pub fn closest_seg_seg(p1: &Vec3, q1: &Vec3, p2: &Vec3, q2: &Vec3) -> (Vec3, Vec3) {
+	let d1 = q1.sub(p1);
+	let d2 = q2.sub(p2);
+	let r = p1.sub(p2);
+	let a = d1.len_sq();
+	let e = d2.len_sq();
+	let f = d2.dot(&r);
+	if a <= EPS && e <= EPS {
+		return (*p1, *p2);
+	}
+	let (s, t) = if a <= EPS {
+		(0.0, (f / e).clamp(0.0, 1.0))
+	} else {
+		let c = d1.dot(&r);
+		if e <= EPS {
+			((-c / a).clamp(0.0, 1.0), 0.0)
+		} else {
+			let b = d1.dot(&d2);
+			let denom = a * e - b * b;
+			let s = if denom.abs() > EPS { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+			let t = (b * s + f) / e;
+			if t < 0.0 {
+				((-c / a).clamp(0.0, 1.0), 0.0)
+			} else if t > 1.0 {
+				(((b - c) / a).clamp(0.0, 1.0), 1.0)
+			} else {
+				(s, t)
+			}
+		}
+	};
+	(p1.add(&d1.mul(s)), p2.add(&d2.mul(t)))
+}
+
+fn signed_dist_box(p: &Vec3, b: &Obb) -> f32 {
+	let l = b.to_local(p);
+	let q = Vec3::new(l.x.abs() - b.half.x, l.y.abs() - b.half.y, l.z.abs() - b.half.z);
+	let outside = Vec3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).len();
+	let inside = q.x.max(q.y).max(q.z).min(0.0);
+	outside + inside
+}
+
+pub fn test_sphere_box(c: &Vec3, r: f32, b: &Obb) -> Option<Collision> {
+	let l = b.to_local(c);
+	let h = b.half;
+	if l.x.abs() > h.x || l.y.abs() > h.y || l.z.abs() > h.z {
+		let p = b.closest_point(c);
+		let d = p.sub(c);
+		let dist_sq = d.len_sq();
+		if dist_sq >= r * r {
+			return None;
+		}
+		let dist = dist_sq.sqrt();
+		let normal = if dist > EPS { d.mul(1.0 / dist) } else { b.center.sub(c).nrm() };
+		return Some(Collision::new(normal, r - dist, p));
+	}
+	let pen = [h.x - l.x.abs(), h.y - l.y.abs(), h.z - l.z.abs()];
+	let loc = [l.x, l.y, l.z];
+	let mut i = 0;
+	for k in 1..3 {
+		if pen[k] < pen[i] {
+			i = k;
+		}
+	}
+	let sign = if loc[i] >= 0.0 { 1.0 } else { -1.0 };
+	let out = b.axes()[i].mul(sign);
+	Some(Collision::new(-out, r + pen[i], c.add(&out.mul(pen[i]))))
+}
+
+pub fn test_sphere_capsule(c: &Vec3, r: f32, p0: &Vec3, p1: &Vec3, cr: f32) -> Option<Collision> {
+	let q = closest_on_seg(c, p0, p1);
+	test_sphere_sphere(c, r, &q, cr)
+}
+
+pub fn test_capsule_capsule(
+	a0: &Vec3, a1: &Vec3, ra: f32,
+	b0: &Vec3, b1: &Vec3, rb: f32
+) -> Option<Collision> {
+	let (pa, pb) = closest_seg_seg(a0, a1, b0, b1);
+	let col = test_sphere_sphere(&pa, ra, &pb, rb)?;
+	let da = a1.sub(a0);
+	let db = b1.sub(b0);
+	let parallel = da.cross(&db).len_sq() <= 1e-6 * da.len_sq() * db.len_sq();
+	if !parallel || da.len_sq() < EPS || db.len_sq() < EPS {
+		return Some(col);
+	}
+	let cands = [
+		(*a0, closest_on_seg(a0, b0, b1)),
+		(*a1, closest_on_seg(a1, b0, b1)),
+		(closest_on_seg(b0, a0, a1), *b0),
+		(closest_on_seg(b1, a0, a1), *b1),
+	];
+	let r_sum = ra + rb;
+	let mut pts: Vec<(f32, Vec3)> = cands
+		.iter()
+		.filter(|(x, y)| x.dist(y) < r_sum)
+		.map(|(x, y)| (x.sub(a0).dot(&da), x.add(&y.sub(x).mul(ra / r_sum))))
+		.collect();
+	if pts.len() < 2 {
+		return Some(col);
+	}
+	pts.sort_by(|p, q| p.0.partial_cmp(&q.0).unwrap_or(std::cmp::Ordering::Equal));
+	let first = pts[0].1;
+	let last = pts[pts.len() - 1].1;
+	if first.dist(&last) < 1e-4 {
+		return Some(col);
+	}
+	Some(col.with_points(vec![first, last]))
+}
+
+pub fn test_capsule_box(p0: &Vec3, p1: &Vec3, r: f32, b: &Obb) -> Option<Collision> {
+	let seg = p1.sub(p0);
+	if seg.len_sq() < EPS {
+		return test_sphere_box(p0, r, b);
+	}
+	let mut lo = 0.0;
+	let mut hi = 1.0;
+	for _ in 0..SEG_BOX_ITER {
+		let m1 = lo + (hi - lo) / 3.0;
+		let m2 = hi - (hi - lo) / 3.0;
+		let d1 = signed_dist_box(&p0.add(&seg.mul(m1)), b);
+		let d2 = signed_dist_box(&p0.add(&seg.mul(m2)), b);
+		if d1 < d2 {
+			hi = m2;
+		} else {
+			lo = m1;
+		}
+	}
+	let best = p0.add(&seg.mul((lo + hi) * 0.5));
+	let col = test_sphere_box(&best, r, b)?;
+	let pts: Vec<Vec3> = [p0, p1]
+		.iter()
+		.filter_map(|p| test_sphere_box(p, r, b))
+		.filter(|c| c.normal.dot(&col.normal) > 0.9)
+		.map(|c| c.point)
+		.collect();
+	if pts.len() >= 2 {
+		Some(col.with_points(pts))
+	} else {
+		Some(col)
+	}
+}
+
+fn box_overlap(axis: &Vec3, aa: &[Vec3; 3], ha: &[f32; 3], ba: &[Vec3; 3], hb: &[f32; 3], t: &Vec3) -> f32 {
+	let mut ra = 0.0;
+	let mut rb = 0.0;
+	for k in 0..3 {
+		ra += ha[k] * aa[k].dot(axis).abs();
+		rb += hb[k] * ba[k].dot(axis).abs();
+	}
+	ra + rb - t.dot(axis).abs()
+}
+
+fn clip_poly(poly: &[Vec3], n: &Vec3, off: f32) -> Vec<Vec3> {
+	let mut out = Vec::with_capacity(poly.len() + 1);
+	for i in 0..poly.len() {
+		let a = poly[i];
+		let b = poly[(i + 1) % poly.len()];
+		let da = n.dot(&a) - off;
+		let db = n.dot(&b) - off;
+		if da <= 0.0 {
+			out.push(a);
+		}
+		if (da <= 0.0) != (db <= 0.0) {
+			let t = da / (da - db);
+			out.push(a.lrp(&b, t));
+		}
+	}
+	out
+}
+
+fn reduce_manifold(pts: Vec<Vec3>, n: &Vec3) -> Vec<Vec3> {
+	if pts.len() <= 4 {
+		return pts;
+	}
+	let far = |from: &Vec3, score: &dyn Fn(&Vec3) -> f32| -> Vec3 {
+		let mut best = *from;
+		let mut best_s = f32::MIN;
+		for p in &pts {
+			let s = score(p);
+			if s > best_s {
+				best_s = s;
+				best = *p;
+			}
+		}
+		best
+	};
+	let p0 = pts[0];
+	let p1 = far(&p0, &|p| p.sub(&p0).len_sq());
+	let p2 = far(&p0, &|p| p.sub(&p0).cross(&p.sub(&p1)).dot(n));
+	let p3 = far(&p0, &|p| -p.sub(&p0).cross(&p.sub(&p1)).dot(n));
+	vec![p0, p2, p1, p3]
+}
+
+fn clip_face(rf: &Obb, i: usize, n: &Vec3, inc: &Obb) -> Vec<Vec3> {
+	let ra = rf.axes();
+	let rh = rf.half_arr();
+	let ia = inc.axes();
+	let ih = inc.half_arr();
+	let mut j = 0;
+	let mut best = -1.0;
+	for (k, ax) in ia.iter().enumerate() {
+		let d = ax.dot(n).abs();
+		if d > best {
+			best = d;
+			j = k;
+		}
+	}
+	let sign = if ia[j].dot(n) > 0.0 { -1.0 } else { 1.0 };
+	let fc = inc.center.add(&ia[j].mul(sign * ih[j]));
+	let eu = ia[(j + 1) % 3].mul(ih[(j + 1) % 3]);
+	let ev = ia[(j + 2) % 3].mul(ih[(j + 2) % 3]);
+	let mut poly = vec![
+		fc.add(&eu).add(&ev),
+		fc.sub(&eu).add(&ev),
+		fc.sub(&eu).sub(&ev),
+		fc.add(&eu).sub(&ev),
+	];
+	for k in 0..3 {
+		if k == i {
+			continue;
+		}
+		for s in [1.0, -1.0] {
+			let pn = ra[k].mul(s);
+			poly = clip_poly(&poly, &pn, pn.dot(&rf.center) + rh[k]);
+			if poly.is_empty() {
+				return poly;
+			}
+		}
+	}
+	let rc = rf.center.add(&n.mul(rh[i]));
+	let pts = poly.iter()
+		.filter_map(|p| {
+			let sep = p.sub(&rc).dot(n);
+			if sep <= 0.0 { Some(p.sub(&n.mul(sep * 0.5))) } else { None }
+		})
+		.collect();
+	reduce_manifold(pts, n)
+}
+
+pub fn test_box_box(a: &Obb, b: &Obb) -> Option<Collision> {
+	let aa = a.axes();
+	let ba = b.axes();
+	let ha = a.half_arr();
+	let hb = b.half_arr();
+	let t = b.center.sub(&a.center);
+	let mut best = f32::MAX;
+	let mut axis = Vec3::UP;
+	let mut kind = (0u8, 0usize, 0usize);
+	for (i, ax) in aa.iter().enumerate() {
+		let o = box_overlap(ax, &aa, &ha, &ba, &hb, &t);
+		if o < 0.0 {
+			return None;
+		}
+		if o < best {
+			best = o;
+			axis = *ax;
+			kind = (0, i, 0);
+		}
+	}
+	for (j, ax) in ba.iter().enumerate() {
+		let o = box_overlap(ax, &aa, &ha, &ba, &hb, &t);
+		if o < 0.0 {
+			return None;
+		}
+		if o < best {
+			best = o;
+			axis = *ax;
+			kind = (1, j, 0);
+		}
+	}
+	let face_best = best;
+	for i in 0..3 {
+		for j in 0..3 {
+			let c = aa[i].cross(&ba[j]);
+			let l = c.len();
+			if l < 1e-4 {
+				continue;
+			}
+			let ax = c.mul(1.0 / l);
+			let o = box_overlap(&ax, &aa, &ha, &ba, &hb, &t);
+			if o < 0.0 {
+				return None;
+			}
+			if o < face_best * EDGE_BIAS && o < best {
+				best = o;
+				axis = ax;
+				kind = (2, i, j);
+			}
+		}
+	}
+	let normal = if t.dot(&axis) < 0.0 { -axis } else { axis };
+	let pts = match kind {
+		(0, i, _) => clip_face(a, i, &normal, b),
+		(1, j, _) => clip_face(b, j, &-normal, a),
+		(_, i, j) => {
+			let mut pa = a.center;
+			let mut pb = b.center;
+			for k in 0..3 {
+				if k != i {
+					let s = if aa[k].dot(&normal) >= 0.0 { 1.0 } else { -1.0 };
+					pa = pa.add(&aa[k].mul(s * ha[k]));
+				}
+				if k != j {
+					let s = if ba[k].dot(&normal) >= 0.0 { -1.0 } else { 1.0 };
+					pb = pb.add(&ba[k].mul(s * hb[k]));
+				}
+			}
+			let ea = aa[i].mul(ha[i]);
+			let eb = ba[j].mul(hb[j]);
+			let (x, y) = closest_seg_seg(&pa.sub(&ea), &pa.add(&ea), &pb.sub(&eb), &pb.add(&eb));
+			vec![x.lrp(&y, 0.5)]
+		}
+	};
+	let fallback = a.center.lrp(&b.center, 0.5);
+	Some(Collision::new(normal, best, fallback).with_points(pts))
+}
+
+fn poly_verts(c: &Collider, pos: &Vec3, rot: &Quat) -> Option<Vec<Vec3>> {
+	let center = c.center(pos, rot);
+	match c.typ {
+		ColliderType::Box => {
+			let h = c.size.mul(0.5);
+			Some((0..8).map(|i| {
+				let l = Vec3::new(
+					if i & 1 != 0 { h.x } else { -h.x },
+					if i & 2 != 0 { h.y } else { -h.y },
+					if i & 4 != 0 { h.z } else { -h.z },
+				);
+				center.add(&rot.mul_vec3(&l))
+			}).collect())
+		}
+		ColliderType::ConvexHull => c.hull.as_ref().map(|h| h.verts.iter().map(|v| center.add(&rot.mul_vec3(v))).collect()),
+		_ => None,
+	}
+}
+
+// vertices within tol of the support plane along n, wound around their middle
+fn feature(verts: &[Vec3], n: &Vec3, tol: f32) -> Vec<Vec3> {
+	let top = verts.iter().map(|v| v.dot(n)).fold(f32::MIN, f32::max);
+	let mut f: Vec<Vec3> = verts.iter().filter(|v| v.dot(n) >= top - tol).copied().collect();
+	if f.len() < 3 {
+		return f;
+	}
+	let c = f.iter().fold(Vec3::ZERO, |acc, p| acc.add(p)).mul(1.0 / f.len() as f32);
+	let u = f.iter().map(|p| p.sub(&c)).find(|d| d.len_sq() > EPS).unwrap_or(Vec3::RIGHT).nrm();
+	let v = n.cross(&u);
+	let ang = |p: &Vec3| {
+		let d = p.sub(&c);
+		det::atan2(d.dot(&v), d.dot(&u))
+	};
+	f.sort_by(|a, b| ang(a).total_cmp(&ang(b)));
+	f
+}
+
+// clips the incident feature to the reference one, n points out of the reference
+fn clip_feature(rf: &[Vec3], inc: &[Vec3], n: &Vec3) -> Vec<Vec3> {
+	let c = rf.iter().fold(Vec3::ZERO, |acc, p| acc.add(p)).mul(1.0 / rf.len() as f32);
+	let mut poly = inc.to_vec();
+	for k in 0..rf.len() {
+		let (a, b) = (rf[k], rf[(k + 1) % rf.len()]);
+		let mut side = b.sub(&a).cross(n);
+		if side.dot(&c.sub(&a)) > 0.0 {
+			side = -side;
+		}
+		if side.len_sq() < EPS {
+			continue;
+		}
+		poly = clip_poly(&poly, &side, side.dot(&a));
+		if poly.is_empty() {
+			return poly;
+		}
+	}
+	let top = rf.iter().map(|p| p.dot(n)).fold(f32::MIN, f32::max);
+	let pts = poly.iter()
+		.filter_map(|p| {
+			let sep = p.dot(n) - top;
+			if sep <= 0.0 { Some(p.sub(&n.mul(sep * 0.5))) } else { None }
+		})
+		.collect();
+	reduce_manifold(pts, n)
+}
+
+// epa yields one point per step, polyhedral pairs swap it for a clipped face manifold so they rest flat
+fn poly_manifold(
+	col: Collision,
+	a: &Collider, pos_a: &Vec3, rot_a: &Quat,
+	b: &Collider, pos_b: &Vec3, rot_b: &Quat
+) -> Collision {
+	let (Some(va), Some(vb)) = (poly_verts(a, pos_a, rot_a), poly_verts(b, pos_b, rot_b)) else {
+		return col;
+	};
+	let n = col.normal;
+	let tol = FEATURE_TOL + col.depth;
+	let fa = feature(&va, &n, tol);
+	let fb = feature(&vb, &-n, tol);
+	let pts = if fa.len() >= 3 && fa.len() >= fb.len() {
+		clip_feature(&fa, &fb, &n)
+	} else if fb.len() >= 3 {
+		clip_feature(&fb, &fa, &-n)
+	} else {
+		Vec::new()
+	};
+	if pts.is_empty() {
+		return col;
+	}
+	col.with_points(pts)
+}
+
+pub fn collide(
+	a: &Collider, pos_a: &Vec3, rot_a: &Quat,
+	b: &Collider, pos_b: &Vec3, rot_b: &Quat
+) -> Option<Collision> {
+	use ColliderType::*;
+	match (a.typ, b.typ) {
+		(ConvexHull, _) | (_, ConvexHull) => test_convex(&Posed::new(a, *pos_a, *rot_a), &Posed::new(b, *pos_b, *rot_b))
+			.map(|c| poly_manifold(c, a, pos_a, rot_a, b, pos_b, rot_b)),
+		(Sphere, Sphere) => test_sphere_sphere(&a.center(pos_a, rot_a), a.radius, &b.center(pos_b, rot_b), b.radius),
+		(Sphere, Box) => test_sphere_box(&a.center(pos_a, rot_a), a.radius, &b.obb(pos_b, rot_b)),
+		(Box, Sphere) => test_sphere_box(&b.center(pos_b, rot_b), b.radius, &a.obb(pos_a, rot_a)).map(Collision::flip),
+		(Sphere, Capsule) => {
+			let (p0, p1) = b.seg(pos_b, rot_b);
+			test_sphere_capsule(&a.center(pos_a, rot_a), a.radius, &p0, &p1, b.radius)
+		}
+		(Capsule, Sphere) => {
+			let (p0, p1) = a.seg(pos_a, rot_a);
+			test_sphere_capsule(&b.center(pos_b, rot_b), b.radius, &p0, &p1, a.radius).map(Collision::flip)
+		}
+		(Capsule, Capsule) => {
+			let (a0, a1) = a.seg(pos_a, rot_a);
+			let (b0, b1) = b.seg(pos_b, rot_b);
+			test_capsule_capsule(&a0, &a1, a.radius, &b0, &b1, b.radius)
+		}
+		(Capsule, Box) => {
+			let (p0, p1) = a.seg(pos_a, rot_a);
+			test_capsule_box(&p0, &p1, a.radius, &b.obb(pos_b, rot_b))
+		}
+		(Box, Capsule) => {
+			let (p0, p1) = b.seg(pos_b, rot_b);
+			test_capsule_box(&p0, &p1, b.radius, &a.obb(pos_a, rot_a)).map(Collision::flip)
+		}
+		(Box, Box) => test_box_box(&a.obb(pos_a, rot_a), &b.obb(pos_b, rot_b)),
+	}
+}
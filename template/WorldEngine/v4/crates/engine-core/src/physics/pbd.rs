@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use crate::math::{Quat, Vec3};
+use super::{layer_bit, narrow, voxel_contacts, AABB, Collider, PhysicsWorld, Voxels, MASK_ALL};
+
+const EPS: f32 = 1e-6;
+
+// maps an editor stiffness in 0..1 onto xpbd compliance, 1 is rigid
+pub fn stiffness_compliance(k: f32) -> f32 {
+	if k >= 1.0 {
+		return 0.0;
+	}
+	let k = k.max(1e-3);
+	(1.0 - k) / k * 1e-3
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SoftConfig {
+	pub substeps: u32,
+	pub iterations: u32,
+	pub stretch: f32,
+	pub bend: f32,
+	pub damping: f32,
+	pub radius: f32,
+	pub friction: f32,
+	pub tear: f32,
+	pub wind: Vec3,
+	pub drag: f32,
+	pub mask: u32,
+}
+
+impl Default for SoftConfig {
+	fn default() -> Self {
+		Self {
+			substeps: 4,
+			iterations: 2,
+			stretch: 0.0,
+			bend: 1e-2,
+			damping: 0.5,
+			radius: 0.05,
+			friction: 0.3,
+			tear: 0.0,
+			wind: Vec3::ZERO,
+			drag: 1.0,
+			mask: MASK_ALL,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Particle {
+	pub pos: Vec3,
+	pub prev: Vec3,
+	pub vel: Vec3,
+	pub mass: f32,
+	pub w: f32,
+}
+
+impl Particle {
+	pub fn new(pos: Vec3, mass: f32) -> Self {
+		Self {
+			pos,
+			prev: pos,
+			vel: Vec3::ZERO,
+			mass,
+			w: if mass > 0.0 { 1.0 / mass } else { 0.0 },
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum SoftCon {
+	Dist { a: usize, b: usize, rest: f32, compliance: f32 },
+	// keeps b at its rest offset from the centroid of a, b, c
+	Bend { a: usize, b: usize, c: usize, rest: f32, compliance: f32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Pin {
+	pub i: usize,
+	pub body: Option<u64>,
+	// local to the body, or a world point without one
+	pub at: Vec3,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoftBody {
+	pub cfg: SoftConfig,
+	pub ps: Vec<Particle>,
+	pub cons: Vec<SoftCon>,
+	pub pins: Vec<Pin>,
+	pub tris: Vec<[u32; 3]>,
+	lambda: Vec<f32>,
+}
+
+impl SoftBody {
+	pub fn new(cfg: SoftConfig) -> Self {
+		Self {
+			cfg,
+			..Default::default()
+		}
+	}
+
+	pub fn rope(start: Vec3, end: Vec3, segs: usize, mass: f32, cfg: SoftConfig) -> Self {
+		let mut s = Self::new(cfg);
+		let segs = segs.max(1);
+		let m = mass / (segs + 1) as f32;
+		for i in 0..=segs {
+			s.add_particle(start.lrp(&end, i as f32 / segs as f32), m);
+		}
+		for i in 0..segs {
+			s.add_dist(i, i + 1, cfg.stretch);
+		}
+		for i in 1..segs {
+			s.add_bend(i - 1, i, i + 1, cfg.bend);
+		}
+		s
+	}
+
+	// grid spanned by u and v from origin, particle (i, j) sits at index j * (seg_x + 1) + i
+	pub fn cloth(origin: Vec3, u: Vec3, v: Vec3, seg_x: usize, seg_y: usize, mass: f32, cfg: SoftConfig) -> Self {
+		let mut s = Self::new(cfg);
+		let (nx, ny) = (seg_x.max(1), seg_y.max(1));
+		let m = mass / ((nx + 1) * (ny + 1)) as f32;
+		for j in 0..=ny {
+			for i in 0..=nx {
+				let p = origin.add(&u.mul(i as f32 / nx as f32)).add(&v.mul(j as f32 / ny as f32));
+				s.add_particle(p, m);
+			}
+		}
+		let at = |i: usize, j: usize| j * (nx + 1) + i;
+		for j in 0..=ny {
+			for i in 0..=nx {
+				if i < nx {
+					s.add_dist(at(i, j), at(i + 1, j), cfg.stretch);
+				}
+				if j < ny {
+					s.add_dist(at(i, j), at(i, j + 1), cfg.stretch);
+				}
+				if i < nx && j < ny {
+					s.add_dist(at(i, j), at(i + 1, j + 1), cfg.stretch);
+					s.add_dist(at(i + 1, j), at(i, j + 1), cfg.stretch);
+					s.tris.push([at(i, j) as u32, at(i + 1, j) as u32, at(i, j + 1) as u32]);
+					s.tris.push([at(i + 1, j) as u32, at(i + 1, j + 1) as u32, at(i, j + 1) as u32]);
+				}
+				if i > 0 && i < nx {
+					s.add_bend(at(i - 1, j), at(i, j), at(i + 1, j), cfg.bend);
+				}
+				if j > 0 && j < ny {
+					s.add_bend(at(i, j - 1), at(i, j), at(i, j + 1), cfg.bend);
+				}
+			}
+		}
+		s
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.ps.len()
+	}
+
+	pub fn add_particle(&mut self, pos: Vec3, mass: f32) -> usize {
+		self.ps.push(Particle::new(pos, mass));
+		self.ps.len() - 1
+	}
+
+	pub fn add_dist(&mut self, a: usize, b: usize, compliance: f32) {
+		let rest = self.ps[a].pos.dist(&self.ps[b].pos);
+		self.cons.push(SoftCon::Dist { a, b, rest, compliance });
+	}
+
+	pub fn add_bend(&mut self, a: usize, b: usize, c: usize, compliance: f32) {
+		let (pa, pb, pc) = (self.ps[a].pos, self.ps[b].pos, self.ps[c].pos);
+		let rest = pb.dist(&pa.add(&pb).add(&pc).mul(1.0 / 3.0));
+		self.cons.push(SoftCon::Bend { a, b, c, rest, compliance });
+	}
+
+	pub fn set_compliance(&mut self, stretch: f32, bend: f32) {
+		self.cfg.stretch = stretch;
+		self.cfg.bend = bend;
+		for con in &mut self.cons {
+			match con {
+				SoftCon::Dist { compliance, .. } => *compliance = stretch,
+				SoftCon::Bend { compliance, .. } => *compliance = bend,
+			}
+		}
+	}
+
+	pub fn pin(&mut self, i: usize, at: Vec3) {
+		self.pin_to(i, None, at);
+	}
+
+	pub fn pin_to(&mut self, i: usize, body: Option<u64>, at: Vec3) {
+		let Some(p) = self.ps.get_mut(i) else {
+			return;
+		};
+		p.w = 0.0;
+		p.vel = Vec3::ZERO;
+		self.pins.retain(|pin| pin.i != i);
+		self.pins.push(Pin { i, body, at });
+	}
+
+	pub fn unpin(&mut self, i: usize) {
+		self.pins.retain(|pin| pin.i != i);
+		if let Some(p) = self.ps.get_mut(i) {
+			p.w = if p.mass > 0.0 { 1.0 / p.mass } else { 0.0 };
+		}
+	}
+
+	pub fn positions(&self) -> Vec<f32> {
+		let mut out = Vec::with_capacity(self.ps.len() * 3);
+		for p in &self.ps {
+			out.extend_from_slice(&[p.pos.x, p.pos.y, p.pos.z]);
+		}
+		out
+	}
+
+	// triangles whose edges are all still connected, so torn cloth opens up
+	pub fn indices(&self) -> Vec<u32> {
+		let edges: HashSet<(usize, usize)> = self.cons.iter()
+			.filter_map(|c| match *c {
+				SoftCon::Dist { a, b, .. } => Some((a.min(b), a.max(b))),
+				_ => None,
+			})
+			.collect();
+		let live = |a: u32, b: u32| edges.contains(&((a.min(b)) as usize, (a.max(b)) as usize));
+		self.tris.iter()
+			.filter(|t| live(t[0], t[1]) && live(t[1], t[2]) && live(t[2], t[0]))
+			.flat_map(|t| t.iter().copied())
+			.collect()
+	}
+
+	pub fn aabb(&self) -> Option<AABB> {
+		let first = self.ps.first()?.pos;
+		let bb = self.ps.iter().fold(AABB::new(first, first), |bb, p| bb.expand(&p.pos));
+		Some(bb.fatten(self.cfg.radius))
+	}
+
+	fn pin_target(world: &PhysicsWorld, pin: &Pin) -> Option<Vec3> {
+		match pin.body {
+			Some(id) => world.get(id).map(|b| b.pos.add(&b.rot.mul_vec3(&pin.at))),
+			None => Some(pin.at),
+		}
+	}
+
+	// per-particle acceleration from wind: cloth catches it on its triangles, ropes just drag
+	fn wind_acc(&self) -> Vec<Vec3> {
+		let mut acc = vec![Vec3::ZERO; self.ps.len()];
+		let wind = self.cfg.wind;
+		if self.cfg.drag <= 0.0 {
+			return acc;
+		}
+		if self.tris.is_empty() {
+			for (a, p) in acc.iter_mut().zip(&self.ps) {
+				*a = wind.sub(&p.vel).mul(self.cfg.drag * p.w);
+			}
+			return acc;
+		}
+		for t in &self.tris {
+			let [i, j, k] = t.map(|i| i as usize);
+			let (a, b, c) = (&self.ps[i], &self.ps[j], &self.ps[k]);
+			let cr = b.pos.sub(&a.pos).cross(&c.pos.sub(&a.pos));
+			let area2 = cr.len();
+			if area2 < EPS {
+				continue;
+			}
+			let n = cr.mul(1.0 / area2);
+			let rel = wind.sub(&a.vel.add(&b.vel).add(&c.vel).mul(1.0 / 3.0));
+			let f = n.mul(self.cfg.drag * rel.dot(&n) * area2 * 0.5 / 3.0);
+			for idx in [i, j, k] {
+				acc[idx] = acc[idx].add(&f.mul(self.ps[idx].w));
+			}
+		}
+		acc
+	}
+
+	// returns how many links tore this step
+	pub fn step(&mut self, world: &PhysicsWorld, vox: Option<&dyn Voxels>, dt: f32) -> usize {
+		if dt <= 0.0 || self.ps.is_empty() {
+			return 0;
+		}
+		let n = self.cfg.substeps.max(1);
+		let h = dt / n as f32;
+		let g = world.cfg.gravity;
+		let wind = self.wind_acc();
+		let targets: Vec<(usize, Vec3)> = self.pins.iter()
+			.filter_map(|pin| Self::pin_target(world, pin).map(|t| (pin.i, t)))
+			.collect();
+		let mut torn = 0;
+		for _ in 0..n {
+			for (p, a) in self.ps.iter_mut().zip(&wind) {
+				p.prev = p.pos;
+				if p.w > 0.0 {
+					p.vel = p.vel.add(&g.add(a).mul(h));
+					p.pos = p.pos.add(&p.vel.mul(h));
+				}
+			}
+			for &(i, t) in &targets {
+				self.ps[i].pos = t;
+			}
+			self.lambda.clear();
+			self.lambda.resize(self.cons.len(), 0.0);
+			for _ in 0..self.cfg.iterations.max(1) {
+				self.solve(h);
+			}
+			self.collide(world, vox);
+			let keep = (1.0 - self.cfg.damping * h).max(0.0);
+			for p in &mut self.ps {
+				p.vel = if p.w > 0.0 { p.pos.sub(&p.prev).mul(keep / h) } else { Vec3::ZERO };
+			}
+			torn += self.tear();
+		}
+		torn
+	}
+
+	fn solve(&mut self, h: f32) {
+		let inv_h2 = 1.0 / (h * h);
+		for (k, con) in self.cons.iter().enumerate() {
+			match *con {
+				SoftCon::Dist { a, b, rest, compliance } => {
+					let (wa, wb) = (self.ps[a].w, self.ps[b].w);
+					let d = self.ps[b].pos.sub(&self.ps[a].pos);
+					let len = d.len();
+					if wa + wb == 0.0 || len < EPS {
+						continue;
+					}
+					let n = d.mul(1.0 / len);
+					let alpha = compliance * inv_h2;
+					let dl = (-(len - rest) - alpha * self.lambda[k]) / (wa + wb + alpha);
+					self.lambda[k] += dl;
+					self.ps[a].pos = self.ps[a].pos.sub(&n.mul(dl * wa));
+					self.ps[b].pos = self.ps[b].pos.add(&n.mul(dl * wb));
+				}
+				SoftCon::Bend { a, b, c, rest, compliance } => {
+					let (wa, wb, wc) = (self.ps[a].w, self.ps[b].w, self.ps[c].w);
+					let ctr = self.ps[a].pos.add(&self.ps[b].pos).add(&self.ps[c].pos).mul(1.0 / 3.0);
+					let d = self.ps[b].pos.sub(&ctr);
+					let len = d.len();
+					if len < EPS {
+						continue;
+					}
+					let n = d.mul(1.0 / len);
+					// gradients are -n/3 for a and c, 2n/3 for b
+					let sum = (wa + wc) / 9.0 + wb * 4.0 / 9.0;
+					let alpha = compliance * inv_h2;
+					if sum + alpha < EPS {
+						continue;
+					}
+					let dl = (-(len - rest) - alpha * self.lambda[k]) / (sum + alpha);
+					self.lambda[k] += dl;
+					self.ps[a].pos = self.ps[a].pos.sub(&n.mul(dl * wa / 3.0));
+					self.ps[c].pos = self.ps[c].pos.sub(&n.mul(dl * wc / 3.0));
+					self.ps[b].pos = self.ps[b].pos.add(&n.mul(dl * wb * 2.0 / 3.0));
+				}
+			}
+		}
+	}
+
+	fn collide(&mut self, world: &PhysicsWorld, vox: Option<&dyn Voxels>) {
+		let r = self.cfg.radius;
+		let ball = Collider::sphere(r);
+		let rot = Quat::identity();
+		let mask = self.cfg.mask;
+		let friction = self.cfg.friction.clamp(0.0, 1.0);
+		for p in self.ps.iter_mut().filter(|p| p.w > 0.0) {
+			let bb = ball.aabb(&p.pos);
+			let mut push = Vec3::ZERO;
+			world.tree().query(&bb, |id| {
+				let Some(body) = world.get(id) else {
+					return true;
+				};
+				let Some(col) = body.col.as_ref().filter(|c| !c.is_trg && mask & layer_bit(c.layer) != 0) else {
+					return true;
+				};
+				if let Some(c) = narrow::collide(&ball, &p.pos.add(&push), &rot, col, &body.pos, &body.rot) {
+					push = push.sub(&c.normal.mul(c.depth));
+				}
+				true
+			});
+			if let Some(v) = vox {
+				// deepest cell only, coplanar faces would stack their pushes
+				let deepest = voxel_contacts(v, &ball, &p.pos.add(&push), &rot, world.cfg.unloaded)
+					.into_iter()
+					.max_by(|a, b| a.depth.total_cmp(&b.depth));
+				if let Some(c) = deepest {
+					push = push.sub(&c.normal.mul(c.depth));
+				}
+			}
+			let depth = push.len();
+			if depth < EPS {
+				continue;
+			}
+			let n = push.mul(1.0 / depth);
+			p.pos = p.pos.add(&push);
+			let mv = p.pos.sub(&p.prev);
+			let slip = mv.sub(&n.mul(mv.dot(&n)));
+			p.pos = p.pos.sub(&slip.mul(friction));
+		}
+	}
+
+	fn tear(&mut self) -> usize {
+		if self.cfg.tear <= 1.0 {
+			return 0;
+		}
+		let limit = self.cfg.tear;
+		let ps = &self.ps;
+		let mut cut: Vec<(usize, usize)> = Vec::new();
+		self.cons.retain(|c| match *c {
+			SoftCon::Dist { a, b, rest, .. } if rest > EPS && ps[a].pos.dist(&ps[b].pos) > rest * limit => {
+				cut.push((a, b));
+				false
+			}
+			_ => true,
+		});
+		if cut.is_empty() {
+			return 0;
+		}
+		let spans = |x: usize, y: usize| cut.iter().any(|&(a, b)| (a == x && b == y) || (a == y && b == x));
+		self.cons.retain(|c| match *c {
+			SoftCon::Bend { a, b, c, .. } => !spans(a, b) && !spans(b, c),
+			_ => true,
+		});
+		cut.len()
+	}
+}
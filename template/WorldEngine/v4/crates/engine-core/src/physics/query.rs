@@ -0,0 +1,185 @@
+use crate::math::{Quat, Vec3};
+use super::{layer_bit, narrow, toi_collider, AABB, Collider, ColliderType, PhysicsWorld};
+
+pub const MASK_ALL: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+	pub id: u64,
+	pub point: Vec3,
+	pub normal: Vec3,
+	pub dist: f32,
+}
+
+pub fn ray_sphere(o: &Vec3, d: &Vec3, max_t: f32, c: &Vec3, r: f32) -> Option<(f32, Vec3)> {
+	let oc = o.sub(c);
+	let b = oc.dot(d);
+	let cc = oc.len_sq() - r * r;
+	if cc < 0.0 {
+		return None;
+	}
+	let h = b * b - cc;
+	if h < 0.0 {
+		return None;
+	}
+	let t = -b - h.sqrt();
+	if t < 0.0 || t > max_t {
+		return None;
+	}
+	Some((t, o.add(&d.mul(t)).sub(c).nrm()))
+}
+
+pub fn ray_capsule(o: &Vec3, d: &Vec3, max_t: f32, p0: &Vec3, p1: &Vec3, r: f32) -> Option<(f32, Vec3)> {
+	if narrow::closest_on_seg(o, p0, p1).sub(o).len_sq() < r * r {
+		return None;
+	}
+	let ba = p1.sub(p0);
+	let oa = o.sub(p0);
+	let baba = ba.dot(&ba);
+	let bard = ba.dot(d);
+	let baoa = ba.dot(&oa);
+	let a = baba - bard * bard;
+	let mut best: Option<f32> = None;
+	if a > 1e-6 {
+		let b = baba * d.dot(&oa) - baoa * bard;
+		let c = baba * oa.len_sq() - baoa * baoa - r * r * baba;
+		let h = b * b - a * c;
+		if h >= 0.0 {
+			let t = (-b - h.sqrt()) / a;
+			let y = baoa + t * bard;
+			if y > 0.0 && y < baba && t >= 0.0 {
+				best = Some(t);
+			}
+		}
+	}
+	for cap in [p0, p1] {
+		if let Some((t, _)) = ray_sphere(o, d, max_t, cap, r) {
+			best = Some(best.map_or(t, |b| b.min(t)));
+		}
+	}
+	let t = best.filter(|&t| t <= max_t)?;
+	let p = o.add(&d.mul(t));
+	Some((t, p.sub(&narrow::closest_on_seg(&p, p0, p1)).nrm()))
+}
+
+pub fn ray_collider(
+	o: &Vec3, d: &Vec3, max_t: f32,
+	col: &Collider, pos: &Vec3, rot: &Quat
+) -> Option<(f32, Vec3)> {
+	match col.typ {
+		ColliderType::Sphere => ray_sphere(o, d, max_t, &col.center(pos, rot), col.radius),
+		ColliderType::Capsule => {
+			let (p0, p1) = col.seg(pos, rot);
+			ray_capsule(o, d, max_t, &p0, &p1, col.radius)
+		}
+		ColliderType::Box => {
+			let obb = col.obb(pos, rot);
+			let inv = rot.conj();
+			let local = AABB::new(obb.half.mul(-1.0), obb.half);
+			let (t, n) = local.ray_hit(&obb.to_local(o), &inv.mul_vec3(d), max_t)?;
+			Some((t, rot.mul_vec3(&n)))
+		}
+		ColliderType::ConvexHull => {
+			let hull = col.hull.as_ref()?;
+			let inv = rot.conj();
+			let c = col.center(pos, rot);
+			let lo = inv.mul_vec3(&o.sub(&c));
+			let ld = inv.mul_vec3(d);
+			let (t, n) = hull.ray(&lo, &ld, max_t)?;
+			Some((t, rot.mul_vec3(&n)))
+		}
+	}
+}
+
+impl PhysicsWorld {
+	fn layer_ok(col: &Collider, mask: u32) -> bool {
+		mask & layer_bit(col.layer) != 0
+	}
+
+	pub fn raycast_all(&self, origin: &Vec3, dir: &Vec3, max_dist: f32, mask: u32) -> Vec<RayHit> {
+		let d = dir.nrm();
+		let mut hits = Vec::new();
+		self.tree().ray(origin, &d, max_dist, |id| {
+			let Some(body) = self.get(id) else {
+				return true;
+			};
+			let Some(col) = body.col.as_ref().filter(|c| Self::layer_ok(c, mask)) else {
+				return true;
+			};
+			if let Some((t, normal)) = ray_collider(origin, &d, max_dist, col, &body.pos, &body.rot) {
+				hits.push(RayHit {
+					id,
+					point: origin.add(&d.mul(t)),
+					normal,
+					dist: t,
+				});
+			}
+			true
+		});
+		hits.sort_by(|a, b| a.dist.total_cmp(&b.dist).then(a.id.cmp(&b.id)));
+		hits
+	}
+
+	pub fn raycast(&self, origin: &Vec3, dir: &Vec3, max_dist: f32, mask: u32) -> Option<RayHit> {
+		self.raycast_all(origin, dir, max_dist, mask).into_iter().next()
+	}
+
+	pub fn sphere_cast(&self, origin: &Vec3, r: f32, dir: &Vec3, max_dist: f32, mask: u32) -> Option<RayHit> {
+		let disp = dir.nrm().mul(max_dist);
+		let rv = Vec3::new(r, r, r);
+		let start = AABB::new(origin.sub(&rv), origin.add(&rv));
+		let end = origin.add(&disp);
+		let swept = start.merge(&AABB::new(end.sub(&rv), end.add(&rv)));
+		let mut best: Option<RayHit> = None;
+		self.tree().query(&swept, |id| {
+			let Some(body) = self.get(id) else {
+				return true;
+			};
+			let Some(col) = body.col.as_ref().filter(|c| Self::layer_ok(c, mask)) else {
+				return true;
+			};
+			let Some(toi) = toi_collider(origin, r, &disp, col, &body.pos, &body.rot, &Vec3::ZERO) else {
+				return true;
+			};
+			let dist = toi.t * max_dist;
+			if best.is_none_or(|b| dist < b.dist || (dist == b.dist && id < b.id)) {
+				best = Some(RayHit {
+					id,
+					point: toi.point,
+					normal: toi.normal,
+					dist,
+				});
+			}
+			true
+		});
+		best
+	}
+
+	fn overlap(&self, probe: &Collider, pos: &Vec3, bb: &AABB, mask: u32) -> Vec<u64> {
+		let rot = Quat::identity();
+		let mut ids = Vec::new();
+		self.tree().query(bb, |id| {
+			let Some(body) = self.get(id) else {
+				return true;
+			};
+			let Some(col) = body.col.as_ref().filter(|c| Self::layer_ok(c, mask)) else {
+				return true;
+			};
+			if narrow::collide(probe, pos, &rot, col, &body.pos, &body.rot).is_some() {
+				ids.push(id);
+			}
+			true
+		});
+		ids.sort_unstable();
+		ids
+	}
+
+	pub fn overlap_sphere(&self, center: &Vec3, r: f32, mask: u32) -> Vec<u64> {
+		let rv = Vec3::new(r, r, r);
+		self.overlap(&Collider::sphere(r), center, &AABB::new(center.sub(&rv), center.add(&rv)), mask)
+	}
+
+	pub fn overlap_aabb(&self, bb: &AABB, mask: u32) -> Vec<u64> {
+		self.overlap(&Collider::box_col(bb.size()), &bb.center(), bb, mask)
+	}
+}
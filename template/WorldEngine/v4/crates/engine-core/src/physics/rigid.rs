@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::math::Vec3;
+use crate::math::{Quat, Vec3};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RigidBodyType {
@@ -16,10 +16,13 @@ pub struct RigidBody {
 	pub ang_vel: Vec3,
 	pub force: Vec3,
 	pub torque: Vec3,
+	pub inv_inertia: Vec3,
 	pub drag: f32,
 	pub ang_drag: f32,
 	pub gravity_scl: f32,
 	pub is_sleeping: bool,
+	pub sleep_timer: f32,
+	pub ccd: bool,
 }
 
 impl Default for RigidBody {
@@ -31,10 +34,13 @@ impl Default for RigidBody {
 			ang_vel: Vec3::ZERO,
 			force: Vec3::ZERO,
 			torque: Vec3::ZERO,
+			inv_inertia: Vec3::ZERO,
 			drag: 0.0,
 			ang_drag: 0.05,
 			gravity_scl: 1.0,
 			is_sleeping: false,
+			sleep_timer: 0.0,
+			ccd: false,
 		}
 	}
 }
@@ -64,39 +70,87 @@ impl RigidBody {
 		}
 	}
 
+	pub fn with_ccd(mut self, ccd: bool) -> Self {
+		self.ccd = ccd;
+		self
+	}
+
+	pub fn wake(&mut self) {
+		self.is_sleeping = false;
+		self.sleep_timer = 0.0;
+	}
+
+	pub fn sleep(&mut self) {
+		self.is_sleeping = true;
+		self.vel = Vec3::ZERO;
+		self.ang_vel = Vec3::ZERO;
+	}
+
 	pub fn add_force(&mut self, force: Vec3) {
 		if self.typ == RigidBodyType::Dynamic {
 			self.force = self.force.add(&force);
+			self.wake();
 		}
 	}
 
 	pub fn add_impulse(&mut self, impulse: Vec3) {
 		if self.typ == RigidBodyType::Dynamic && self.mass > 0.0 {
 			self.vel = self.vel.add(&impulse.mul(1.0 / self.mass));
+			self.wake();
 		}
 	}
 
 	pub fn add_torque(&mut self, torque: Vec3) {
 		if self.typ == RigidBodyType::Dynamic {
 			self.torque = self.torque.add(&torque);
+			self.wake();
+		}
+	}
+
+	pub fn set_inertia(&mut self, inertia: &Vec3) {
+		let inv = |i: f32| if i > 0.0 { 1.0 / i } else { 0.0 };
+		self.inv_inertia = Vec3::new(inv(inertia.x), inv(inertia.y), inv(inertia.z));
+	}
+
+	pub fn mul_inv_inertia(&self, rot: &Quat, v: &Vec3) -> Vec3 {
+		if self.typ != RigidBodyType::Dynamic {
+			return Vec3::ZERO;
+		}
+		let l = rot.conj().mul_vec3(v);
+		rot.mul_vec3(&Vec3::new(l.x * self.inv_inertia.x, l.y * self.inv_inertia.y, l.z * self.inv_inertia.z))
+	}
+
+	pub fn add_impulse_at(&mut self, impulse: &Vec3, r: &Vec3, rot: &Quat) {
+		if self.typ == RigidBodyType::Dynamic && self.mass > 0.0 {
+			self.vel = self.vel.add(&impulse.mul(1.0 / self.mass));
+			self.ang_vel = self.ang_vel.add(&self.mul_inv_inertia(rot, &r.cross(impulse)));
+			self.wake();
 		}
 	}
 
-	pub fn integrate(&mut self, dt: f32, gravity: &Vec3) {
+	pub fn integrate(&mut self, dt: f32, gravity: &Vec3, rot: &Quat) {
 		if self.typ != RigidBodyType::Dynamic || self.is_sleeping {
 			return;
 		}
 		let grav = gravity.mul(self.gravity_scl);
-		let accel = self.force.mul(1.0 / self.mass).add(&grav);
+		let accel = self.force.mul(self.inv_mass()).add(&grav);
 		self.vel = self.vel.add(&accel.mul(dt));
 		self.vel = self.vel.mul(1.0 - self.drag * dt);
-		let ang_accel = self.torque.mul(1.0 / self.mass);
+		let ang_accel = self.mul_inv_inertia(rot, &self.torque);
 		self.ang_vel = self.ang_vel.add(&ang_accel.mul(dt));
 		self.ang_vel = self.ang_vel.mul(1.0 - self.ang_drag * dt);
 		self.force = Vec3::ZERO;
 		self.torque = Vec3::ZERO;
 	}
 
+	pub fn inv_mass(&self) -> f32 {
+		if self.typ == RigidBodyType::Dynamic && self.mass > 0.0 {
+			1.0 / self.mass
+		} else {
+			0.0
+		}
+	}
+
 	pub fn speed(&self) -> f32 {
 		self.vel.len()
 	}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use crate::math::Vec3;
+use super::{Body, Collision, PhysMat, PhysicsConfig, TERRAIN_ID};
+
+const MATCH_DIST_SQ: f32 = 0.01;
+pub(crate) const BOUNCE_VEL: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+struct ContactPt {
+	local: Vec3,
+	ra: Vec3,
+	rb: Vec3,
+	mass_n: f32,
+	mass_t: [f32; 2],
+	bias: f32,
+	jn: f32,
+	jt: [f32; 2],
+}
+
+struct Manifold {
+	a: usize,
+	b: Option<usize>,
+	key: (u64, u64),
+	n: Vec3,
+	t: [Vec3; 2],
+	friction: f32,
+	inv_a: f32,
+	inv_b: f32,
+	pts: Vec<ContactPt>,
+}
+
+impl Manifold {
+	fn apply(&self, bodies: &mut [Body], pt: &ContactPt, imp: &Vec3) {
+		let a = &mut bodies[self.a];
+		a.rb.vel = a.rb.vel.sub(&imp.mul(self.inv_a));
+		a.rb.ang_vel = a.rb.ang_vel.sub(&a.rb.mul_inv_inertia(&a.rot, &pt.ra.cross(imp)));
+		let Some(b) = self.b.map(|i| &mut bodies[i]) else {
+			return;
+		};
+		b.rb.vel = b.rb.vel.add(&imp.mul(self.inv_b));
+		b.rb.ang_vel = b.rb.ang_vel.add(&b.rb.mul_inv_inertia(&b.rot, &pt.rb.cross(imp)));
+	}
+
+	fn rel_vel(&self, bodies: &[Body], pt: &ContactPt) -> Vec3 {
+		let a = &bodies[self.a].rb;
+		let va = a.vel.add(&a.ang_vel.cross(&pt.ra));
+		let vb = self.b.map_or(Vec3::ZERO, |i| {
+			let b = &bodies[i].rb;
+			b.vel.add(&b.ang_vel.cross(&pt.rb))
+		});
+		vb.sub(&va)
+	}
+
+	fn eff_mass(&self, bodies: &[Body], ra: &Vec3, rb: &Vec3, dir: &Vec3) -> f32 {
+		let a = &bodies[self.a];
+		let ang_a = a.rb.mul_inv_inertia(&a.rot, &ra.cross(dir)).cross(ra);
+		let ang_b = self.b.map_or(Vec3::ZERO, |i| {
+			let b = &bodies[i];
+			b.rb.mul_inv_inertia(&b.rot, &rb.cross(dir)).cross(rb)
+		});
+		let k = self.inv_a + self.inv_b + dir.dot(&ang_a.add(&ang_b));
+		if k > 0.0 { 1.0 / k } else { 0.0 }
+	}
+}
+
+fn tangents(n: &Vec3) -> [Vec3; 2] {
+	let axis = if n.x.abs() < 0.57 { Vec3::RIGHT } else { Vec3::FORWARD };
+	let t0 = n.cross(&axis).nrm();
+	[t0, n.cross(&t0)]
+}
+
+#[derive(Default)]
+pub(crate) struct ContactSolver {
+	manifolds: Vec<Manifold>,
+	cache: HashMap<(u64, u64), Vec<ContactPt>>,
+}
+
+impl ContactSolver {
+	fn mat(body: &Body, cfg: &PhysicsConfig) -> PhysMat {
+		body.col
+			.as_ref()
+			.and_then(|c| c.mat)
+			.unwrap_or(PhysMat {
+				friction: cfg.friction,
+				bounce: cfg.restitution,
+				..Default::default()
+			})
+	}
+
+	pub fn prepare<'a>(
+		&mut self,
+		bodies: &[Body], idx: &HashMap<u64, usize>,
+		contacts: impl Iterator<Item = &'a Collision>, cfg: &PhysicsConfig, dt: f32
+	) {
+		self.manifolds.clear();
+		for col in contacts {
+			let Some(&a) = idx.get(&col.ent_a) else {
+				continue;
+			};
+			// terrain stands in as an immovable body with no index
+			let b = match col.ent_b {
+				TERRAIN_ID => None,
+				id => match idx.get(&id) {
+					Some(&b) => Some(b),
+					None => continue,
+				},
+			};
+			let inv_a = bodies[a].rb.inv_mass();
+			let inv_b = b.map_or(0.0, |b| bodies[b].rb.inv_mass());
+			if inv_a + inv_b <= 0.0 {
+				continue;
+			}
+			let mat_a = Self::mat(&bodies[a], cfg);
+			let mat_b = b.map_or(cfg.terrain_mat, |b| Self::mat(&bodies[b], cfg));
+			let pos_b = b.map_or(col.point, |b| bodies[b].pos);
+			let key = (col.ent_a, col.ent_b);
+			let mut m = Manifold {
+				a,
+				b,
+				key,
+				n: col.normal,
+				t: tangents(&col.normal),
+				friction: mat_a.combine_friction(&mat_b),
+				inv_a,
+				inv_b,
+				pts: Vec::with_capacity(col.points.len()),
+			};
+			let bounce = mat_a.combine_bounce(&mat_b);
+			let pen_bias = cfg.baumgarte / dt * (col.depth - cfg.slop).max(0.0);
+			let inv_rot = bodies[a].rot.conj();
+			let cached = self.cache.get(&key);
+			for p in &col.points {
+				let local = inv_rot.mul_vec3(&p.sub(&bodies[a].pos));
+				let ra = p.sub(&bodies[a].pos);
+				let rb = p.sub(&pos_b);
+				let mut pt = ContactPt {
+					local,
+					ra,
+					rb,
+					mass_n: m.eff_mass(bodies, &ra, &rb, &m.n),
+					mass_t: [m.eff_mass(bodies, &ra, &rb, &m.t[0]), m.eff_mass(bodies, &ra, &rb, &m.t[1])],
+					bias: pen_bias,
+					jn: 0.0,
+					jt: [0.0; 2],
+				};
+				let vn = m.rel_vel(bodies, &pt).dot(&m.n);
+				if vn < -BOUNCE_VEL {
+					pt.bias = pt.bias.max(-bounce * vn);
+				}
+				if cfg.warm_start {
+					if let Some(old) = cached.and_then(|c| c.iter().find(|o| o.local.sub(&local).len_sq() < MATCH_DIST_SQ)) {
+						pt.jn = old.jn;
+						pt.jt = old.jt;
+					}
+				}
+				m.pts.push(pt);
+			}
+			self.manifolds.push(m);
+		}
+	}
+
+	pub fn warm(&self, bodies: &mut [Body]) {
+		for m in &self.manifolds {
+			for pt in &m.pts {
+				let imp = m.n.mul(pt.jn).add(&m.t[0].mul(pt.jt[0])).add(&m.t[1].mul(pt.jt[1]));
+				m.apply(bodies, pt, &imp);
+			}
+		}
+	}
+
+	pub fn solve(&mut self, bodies: &mut [Body]) {
+		for m in &mut self.manifolds {
+			for i in 0..m.pts.len() {
+				let mut pt = m.pts[i];
+				let vn = m.rel_vel(bodies, &pt).dot(&m.n);
+				let old = pt.jn;
+				pt.jn = (old + pt.mass_n * (pt.bias - vn)).max(0.0);
+				m.apply(bodies, &pt, &m.n.mul(pt.jn - old));
+				let lim = m.friction * pt.jn;
+				for k in 0..2 {
+					let vt = m.rel_vel(bodies, &pt).dot(&m.t[k]);
+					let old = pt.jt[k];
+					pt.jt[k] = (old - pt.mass_t[k] * vt).clamp(-lim, lim);
+					m.apply(bodies, &pt, &m.t[k].mul(pt.jt[k] - old));
+				}
+				m.pts[i] = pt;
+			}
+		}
+	}
+
+	// normal impulse summed per manifold, with the point it was weighted toward
+	pub fn impulses(&self, bodies: &[Body]) -> Vec<(u64, u64, f32, Vec3)> {
+		self.manifolds
+			.iter()
+			.filter_map(|m| {
+				let j: f32 = m.pts.iter().map(|pt| pt.jn).sum();
+				if j <= 0.0 {
+					return None;
+				}
+				let pos = bodies[m.a].pos;
+				let p = m.pts.iter().fold(Vec3::ZERO, |acc, pt| acc.add(&pos.add(&pt.ra).mul(pt.jn / j)));
+				Some((m.key.0, m.key.1, j, p))
+			})
+			.collect()
+	}
+
+	pub fn store(&mut self) {
+		self.cache.clear();
+		for m in self.manifolds.drain(..) {
+			self.cache.entry(m.key).or_default().extend(m.pts);
+		}
+	}
+
+	pub fn clr(&mut self) {
+		self.manifolds.clear();
+		self.cache.clear();
+	}
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use crate::ecs::{EntityId, System, SystemStage, World};
+use crate::math::{Quat, Transform, Vec3};
+use super::{Body, Collider, PhysicsConfig, PhysicsEvent, PhysicsWorld, RigidBody};
+
+// last two fixed step poses for rendering between steps, Transform holds the current one
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicsInterp {
+	pub prev_pos: Vec3,
+	pub prev_rot: Quat,
+	pub pos: Vec3,
+	pub rot: Quat,
+	pub alpha: f32,
+}
+
+impl PhysicsInterp {
+	pub fn lrp(&self) -> (Vec3, Quat) {
+		(self.prev_pos.lrp(&self.pos, self.alpha), self.prev_rot.slerp(&self.rot, self.alpha))
+	}
+}
+
+fn moved(body: &Body, xf: &Transform) -> bool {
+	let (p, q) = (xf.pos, xf.rot);
+	[p.x, p.y, p.z, q.x, q.y, q.z, q.w] != [body.pos.x, body.pos.y, body.pos.z, body.rot.x, body.rot.y, body.rot.z, body.rot.w]
+}
+
+pub struct PhysicsSystem {
+	pub phys: PhysicsWorld,
+	events: Vec<PhysicsEvent>,
+}
+
+impl Default for PhysicsSystem {
+	fn default() -> Self {
+		Self::new(PhysicsConfig::default())
+	}
+}
+
+impl PhysicsSystem {
+	pub fn new(cfg: PhysicsConfig) -> Self {
+		Self {
+			phys: PhysicsWorld::new(cfg),
+			events: Vec::new(),
+		}
+	}
+
+	pub fn drain_events(&mut self) -> Vec<PhysicsEvent> {
+		std::mem::take(&mut self.events)
+	}
+
+	pub fn alpha(&self) -> f32 {
+		self.phys.alpha()
+	}
+
+	fn sync_in(&mut self, world: &World) -> Vec<EntityId> {
+		let mut ids = world.cmp.entities_with::<RigidBody>();
+		ids.sort_by_key(|e| e.0);
+		let stale: Vec<u64> = self.phys
+			.bodies()
+			.iter()
+			.map(|b| b.id)
+			.filter(|id| ids.binary_search_by_key(id, |e| e.0).is_err())
+			.collect();
+		for id in stale {
+			self.phys.del(id);
+		}
+		for &ent in &ids {
+			let Some(rb) = world.cmp.get::<RigidBody>(ent) else {
+				continue;
+			};
+			let xf = world.cmp.get::<Transform>(ent).copied().unwrap_or_default();
+			let col = world.cmp.get::<Collider>(ent).cloned();
+			match self.phys.get_mut(ent.0) {
+				Some(body) => {
+					// a transform that no longer matches the body was moved by hand, don't smear it
+					if moved(body, &xf) {
+						body.pos = xf.pos;
+						body.rot = xf.rot;
+						body.snap();
+					}
+					body.rb = rb.clone();
+					body.col = col;
+					body.upd_inertia();
+					// teleports and collider edits move the proxy now, not after the next step
+					self.phys.refresh(ent.0);
+				}
+				None => {
+					let mut body = Body::new(ent.0, xf.pos, rb.clone()).with_rot(xf.rot);
+					body.col = col;
+					self.phys.add(body);
+				}
+			}
+		}
+		ids
+	}
+
+	// fragments come out of the step under physics ids, give each its own entity and drop the broken one
+	fn adopt_frags(&mut self, world: &mut World, events: &mut [PhysicsEvent], ids: &mut Vec<EntityId>) {
+		// a tick runs several steps, so a fragment adopted here can break again later in the list
+		let mut adopted: HashMap<u64, u64> = HashMap::new();
+		for ev in events {
+			let PhysicsEvent::Fracture { id, first, cnt } = ev else {
+				continue;
+			};
+			if let Some(&e) = adopted.get(id) {
+				*id = e;
+			}
+			// ids stays sorted since spawn only counts up
+			if ids.binary_search_by_key(id, |e| e.0).is_ok() {
+				world.despawn(EntityId::new(*id));
+			}
+			let mut ents = Vec::with_capacity(*cnt as usize);
+			for k in 0..*cnt as u64 {
+				let cfg = self.phys.breakable(*first + k).copied();
+				let Some(mut body) = self.phys.del(*first + k) else {
+					continue;
+				};
+				let ent = world.spawn();
+				adopted.insert(body.id, ent.0);
+				body.id = ent.0;
+				world.cmp.add(ent, body.rb.clone());
+				if let Some(col) = &body.col {
+					world.cmp.add(ent, col.clone());
+				}
+				self.phys.add(body);
+				if let Some(cfg) = cfg {
+					self.phys.set_breakable(ent.0, cfg);
+				}
+				ents.push(ent);
+			}
+			// spawn hands out ids in order, so the fragments stay one run
+			if let Some(e) = ents.first() {
+				*first = e.0;
+			}
+			*cnt = ents.len() as u32;
+			ids.extend(ents);
+		}
+	}
+
+	fn sync_out(&self, world: &mut World, ids: &[EntityId]) {
+		let alpha = self.phys.alpha();
+		for &ent in ids {
+			let Some(body) = self.phys.get(ent.0) else {
+				continue;
+			};
+			if let Some(rb) = world.cmp.get_mut::<RigidBody>(ent) {
+				*rb = body.rb.clone();
+			}
+			match world.cmp.get_mut::<Transform>(ent) {
+				Some(xf) => {
+					xf.pos = body.pos;
+					xf.rot = body.rot;
+				}
+				None => {
+					world.cmp.add(ent, Transform::new(body.pos).with_rot(body.rot));
+				}
+			}
+			world.cmp.add(ent, PhysicsInterp {
+				prev_pos: body.prev_pos,
+				prev_rot: body.prev_rot,
+				pos: body.pos,
+				rot: body.rot,
+				alpha,
+			});
+		}
+	}
+}
+
+impl System for PhysicsSystem {
+	fn name(&self) -> &'static str {
+		"physics"
+	}
+
+	fn stage(&self) -> SystemStage {
+		SystemStage::Update
+	}
+
+	fn run(&mut self, world: &mut World, dt: f32) {
+		let mut ids = self.sync_in(world);
+		// fixed steps out of the frame time, renderers blend with PhysicsInterp
+		self.phys.tick(dt);
+		let mut events = self.phys.drain_events();
+		self.adopt_frags(world, &mut events, &mut ids);
+		self.sync_out(world, &ids);
+		self.events.extend(events);
+	}
+}
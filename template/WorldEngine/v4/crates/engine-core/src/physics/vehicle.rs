@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use crate::math::{det, Quat, Vec3};
+use super::character::up_rot;
+use super::{voxel_ray, PhysicsWorld, Voxels, MASK_ALL};
+
+const RPM: f32 = 60.0 / std::f32::consts::TAU;
+// below this speed slip is measured against a floor so a parked car doesn't divide by zero
+const SLIP_VEL: f32 = 1.0;
+
+// pacejka magic formula, x is slip ratio or slip angle in radians
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TireCurve {
+	pub b: f32,
+	pub c: f32,
+	pub d: f32,
+	pub e: f32,
+}
+
+impl TireCurve {
+	pub const LONG: Self = Self { b: 10.0, c: 1.9, d: 1.0, e: 0.97 };
+	pub const LAT: Self = Self { b: 10.0, c: 1.3, d: 1.0, e: 0.97 };
+
+	pub fn eval(&self, x: f32) -> f32 {
+		let bx = self.b * x;
+		self.d * det::sin(self.c * det::atan(bx - self.e * (bx - det::atan(bx))))
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WheelConfig {
+	// suspension top, local to the chassis
+	pub pos: Vec3,
+	pub radius: f32,
+	pub rest: f32,
+	pub stiffness: f32,
+	pub damping: f32,
+	pub mass: f32,
+	pub steer: bool,
+	pub drive: bool,
+	pub handbrake: bool,
+}
+
+impl Default for WheelConfig {
+	fn default() -> Self {
+		Self {
+			pos: Vec3::ZERO,
+			radius: 0.4,
+			rest: 0.3,
+			stiffness: 30000.0,
+			damping: 4000.0,
+			mass: 20.0,
+			steer: false,
+			drive: false,
+			handbrake: false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Wheel {
+	pub cfg: WheelConfig,
+	pub len: f32,
+	pub contact: bool,
+	pub point: Vec3,
+	pub normal: Vec3,
+	pub ground: Option<u64>,
+	pub spin: f32,
+	pub angle: f32,
+	pub steer: f32,
+	pub load: f32,
+	pub slip_ratio: f32,
+	pub slip_angle: f32,
+}
+
+impl Wheel {
+	pub fn new(cfg: WheelConfig) -> Self {
+		Self {
+			cfg,
+			len: cfg.rest,
+			contact: false,
+			point: Vec3::ZERO,
+			normal: Vec3::UP,
+			ground: None,
+			spin: 0.0,
+			angle: 0.0,
+			steer: 0.0,
+			load: 0.0,
+			slip_ratio: 0.0,
+			slip_angle: 0.0,
+		}
+	}
+
+	fn inertia(&self) -> f32 {
+		0.5 * self.cfg.mass * self.cfg.radius * self.cfg.radius
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleConfig {
+	pub grip: f32,
+	pub engine_torque: f32,
+	pub engine_inertia: f32,
+	pub max_rpm: f32,
+	pub idle_rpm: f32,
+	pub gears: Vec<f32>,
+	pub reverse: f32,
+	pub final_drive: f32,
+	pub auto: bool,
+	pub shift_up: f32,
+	pub shift_down: f32,
+	pub brake_torque: f32,
+	pub handbrake_torque: f32,
+	pub max_steer: f32,
+	pub steer_spd: f32,
+	pub long: TireCurve,
+	pub lat: TireCurve,
+	pub mask: u32,
+}
+
+impl Default for VehicleConfig {
+	fn default() -> Self {
+		Self {
+			grip: 1.0,
+			engine_torque: 300.0,
+			engine_inertia: 0.25,
+			max_rpm: 6500.0,
+			idle_rpm: 900.0,
+			gears: vec![3.2, 2.1, 1.5, 1.1, 0.9],
+			reverse: 3.0,
+			final_drive: 3.4,
+			auto: true,
+			shift_up: 0.85,
+			shift_down: 0.35,
+			brake_torque: 3000.0,
+			handbrake_torque: 5000.0,
+			max_steer: 30f32.to_radians(),
+			steer_spd: 3.0,
+			long: TireCurve::LONG,
+			lat: TireCurve::LAT,
+			mask: MASK_ALL,
+		}
+	}
+}
+
+// drives a dynamic chassis body: +y forward, +x right, +z up in chassis space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vehicle {
+	pub body: u64,
+	pub cfg: VehicleConfig,
+	pub wheels: Vec<Wheel>,
+	pub throttle: f32,
+	pub brake: f32,
+	pub steer: f32,
+	pub handbrake: bool,
+	pub gear: i32,
+	pub rpm: f32,
+	pub speed: f32,
+}
+
+impl Vehicle {
+	pub fn new(body: u64, cfg: VehicleConfig) -> Self {
+		Self {
+			body,
+			cfg,
+			wheels: Vec::new(),
+			throttle: 0.0,
+			brake: 0.0,
+			steer: 0.0,
+			handbrake: false,
+			gear: 1,
+			rpm: 0.0,
+			speed: 0.0,
+		}
+	}
+
+	pub fn with_wheel(mut self, cfg: WheelConfig) -> Self {
+		self.add_wheel(cfg);
+		self
+	}
+
+	pub fn add_wheel(&mut self, cfg: WheelConfig) -> usize {
+		self.wheels.push(Wheel::new(cfg));
+		self.wheels.len() - 1
+	}
+
+	// four wheels at the corners of a w by l footprint, steering up front and driven at the back
+	pub fn car(body: u64, cfg: VehicleConfig, w: f32, l: f32, h: f32, wheel: WheelConfig) -> Self {
+		let (x, y) = (w * 0.5, l * 0.5);
+		let mut v = Self::new(body, cfg);
+		for (px, py) in [(-x, y), (x, y), (-x, -y), (x, -y)] {
+			let front = py > 0.0;
+			v.add_wheel(WheelConfig {
+				pos: Vec3::new(px, py, h),
+				steer: front,
+				drive: !front,
+				handbrake: !front,
+				..wheel
+			});
+		}
+		v
+	}
+
+	pub fn set_input(&mut self, throttle: f32, brake: f32, steer: f32, handbrake: bool) {
+		self.throttle = throttle.clamp(-1.0, 1.0);
+		self.brake = brake.clamp(0.0, 1.0);
+		self.steer = steer.clamp(-1.0, 1.0);
+		self.handbrake = handbrake;
+	}
+
+	pub fn shift(&mut self, gear: i32) {
+		self.gear = gear.clamp(-1, self.cfg.gears.len() as i32);
+	}
+
+	fn ratio(&self) -> f32 {
+		let g = match self.gear {
+			0 => 0.0,
+			g if g < 0 => -self.cfg.reverse,
+			g => self.cfg.gears.get(g as usize - 1).copied().unwrap_or(0.0),
+		};
+		g * self.cfg.final_drive
+	}
+
+	// flat to 70% of the rev range, then falling to nothing at the limiter
+	fn torque(&self) -> f32 {
+		let knee = self.cfg.max_rpm * 0.7;
+		let f = if self.rpm <= knee { 1.0 } else { ((self.cfg.max_rpm - self.rpm) / (self.cfg.max_rpm - knee)).max(0.0) };
+		self.cfg.engine_torque * f
+	}
+
+	// shifts on road speed rather than engine rpm so wheelspin doesn't run up the gearbox
+	fn auto_shift(&mut self, road_rpm: f32) {
+		if !self.cfg.auto {
+			return;
+		}
+		let top = self.cfg.gears.len() as i32;
+		// pulling back while nearly stopped selects reverse, pushing forward leaves it
+		if self.speed.abs() < 1.0 {
+			if self.throttle < 0.0 && self.gear >= 0 {
+				self.gear = -1;
+			} else if self.throttle > 0.0 && self.gear <= 0 {
+				self.gear = 1;
+			}
+		}
+		if self.gear > 0 {
+			if road_rpm > self.cfg.max_rpm * self.cfg.shift_up && self.gear < top {
+				self.gear += 1;
+			} else if road_rpm < self.cfg.max_rpm * self.cfg.shift_down && self.gear > 1 {
+				self.gear -= 1;
+			}
+		}
+	}
+
+	fn cast(world: &PhysicsWorld, vox: Option<&dyn Voxels>, skip: u64, mask: u32, o: &Vec3, d: &Vec3, max: f32) -> Option<(f32, Vec3, Option<u64>)> {
+		let body = world.raycast_all(o, d, max, mask)
+			.into_iter()
+			.find(|h| h.id != skip && !world.get(h.id).is_some_and(|b| b.is_trg()))
+			.map(|h| (h.dist, h.normal, Some(h.id)));
+		let terrain = vox.and_then(|v| voxel_ray(v, o, d, max, world.cfg.unloaded)).map(|(t, n)| (t, n, None));
+		match (body, terrain) {
+			(Some(a), Some(b)) => Some(if a.0 <= b.0 { a } else { b }),
+			(a, b) => a.or(b),
+		}
+	}
+
+	// call before every fixed world step with that step's dt, e.g. from tick_each, forces land on the chassis
+	pub fn step(&mut self, world: &mut PhysicsWorld, vox: Option<&dyn Voxels>, dt: f32) {
+		if dt <= 0.0 {
+			return;
+		}
+		let Some(chassis) = world.get(self.body).filter(|b| b.is_dynamic()) else {
+			return;
+		};
+		let (pos, vel, w, mass) = (chassis.pos, chassis.rb.vel, chassis.rb.ang_vel, chassis.rb.mass);
+		let rot = Self::frame(world, &chassis.rot);
+		let up = rot.mul_vec3(&Vec3::UP);
+		let fwd = rot.mul_vec3(&Vec3::FORWARD);
+		self.speed = vel.dot(&fwd);
+		let target = self.steer * self.cfg.max_steer;
+		let ratio = self.ratio();
+		let driven = self.wheels.iter().filter(|w| w.cfg.drive).count().max(1) as f32;
+		let touching = self.wheels.iter().filter(|w| w.contact).count().max(1) as f32;
+		// each wheel may stop at most its share of the chassis within one step
+		let share = mass / touching / dt;
+		let drive = if ratio != 0.0 { self.torque() * ratio * self.throttle.abs() / driven } else { 0.0 };
+		let mut force = Vec3::ZERO;
+		let mut torque = Vec3::ZERO;
+		let mut reactions: Vec<(u64, Vec3, Vec3)> = Vec::new();
+		let mut wheel_rpm = 0.0;
+		let mut road_rpm = 0.0;
+		for wheel in &mut self.wheels {
+			let c = wheel.cfg;
+			if c.steer {
+				let step = self.cfg.steer_spd * dt;
+				wheel.steer += (target - wheel.steer).clamp(-step, step);
+			}
+			let top = pos.add(&rot.mul_vec3(&c.pos));
+			let reach = c.rest + c.radius;
+			let hit = Self::cast(world, vox, self.body, self.cfg.mask, &top, &up.mul(-1.0), reach);
+			let prev_len = wheel.len;
+			wheel.contact = hit.is_some();
+			wheel.load = 0.0;
+			wheel.slip_ratio = 0.0;
+			wheel.slip_angle = 0.0;
+			let brake = self.brake * self.cfg.brake_torque + if self.handbrake && c.handbrake { self.cfg.handbrake_torque } else { 0.0 };
+			// the flywheel seen through the gearbox dominates a driven wheel's inertia in low gears
+			let i_w = wheel.inertia() + if c.drive { self.cfg.engine_inertia * ratio * ratio / driven } else { 0.0 };
+			let drive = if c.drive { drive } else { 0.0 };
+			if let Some((t, n, ground)) = hit {
+				wheel.len = (t - c.radius).clamp(0.0, c.rest);
+				wheel.point = top.sub(&up.mul(t));
+				wheel.normal = n;
+				wheel.ground = ground;
+				let r = wheel.point.sub(&pos);
+				let pv = vel.add(&w.cross(&r));
+				let spring = c.stiffness * (c.rest - wheel.len);
+				let damp = c.damping * (prev_len - wheel.len) / dt;
+				let load = (spring + damp).max(0.0);
+				wheel.load = load;
+				let wf = Quat::from_axis_angle(&up, -wheel.steer).mul_vec3(&fwd);
+				let f_dir = wf.sub(&n.mul(wf.dot(&n))).nrm();
+				let s_dir = f_dir.cross(&n);
+				let (vx, vy) = (pv.dot(&f_dir), pv.dot(&s_dir));
+				let denom = vx.abs().max(SLIP_VEL);
+				wheel.slip_ratio = (wheel.spin * c.radius - vx) / denom;
+				wheel.slip_angle = det::atan2(vy, denom);
+				let mu = self.cfg.grip * load;
+				let mut fx = self.cfg.long.eval(wheel.slip_ratio) * mu;
+				let mut fy = -self.cfg.lat.eval(wheel.slip_angle) * mu;
+				// trim to the friction circle, then to what stops this corner in one step
+				let len = (fx * fx + fy * fy).sqrt();
+				if len > mu && len > 0.0 {
+					fx *= mu / len;
+					fy *= mu / len;
+				}
+				let slip_v = (wheel.spin * c.radius - vx).abs();
+				fx = fx.clamp(-slip_v * share, slip_v * share);
+				fy = fy.clamp(-vy.abs() * share, vy.abs() * share);
+				let f = up.mul(load).add(&f_dir.mul(fx)).add(&s_dir.mul(fy));
+				force = force.add(&f);
+				torque = torque.add(&r.cross(&f));
+				if let Some(id) = ground {
+					reactions.push((id, f.mul(-1.0), wheel.point));
+				}
+				wheel.spin = Self::spin(wheel.spin, drive, fx * c.radius, brake, i_w, dt, Some(vx / c.radius));
+			} else {
+				wheel.len = c.rest;
+				wheel.ground = None;
+				wheel.spin = Self::spin(wheel.spin, drive, 0.0, brake, i_w, dt, None);
+			}
+			wheel.angle = (wheel.angle + wheel.spin * dt) % std::f32::consts::TAU;
+			if c.drive {
+				wheel_rpm += wheel.spin.abs() * RPM / driven;
+				road_rpm += self.speed.abs() / c.radius * RPM / driven;
+			}
+		}
+		self.rpm = (wheel_rpm * ratio.abs()).max(self.cfg.idle_rpm).min(self.cfg.max_rpm);
+		self.auto_shift(road_rpm * ratio.abs());
+		// reverse throttle outside reverse gear brakes instead
+		if self.throttle < 0.0 && self.gear > 0 || self.throttle > 0.0 && self.gear < 0 {
+			self.brake = self.brake.max(self.throttle.abs());
+		}
+		if let Some(body) = world.get_mut(self.body) {
+			body.rb.add_force(force);
+			body.rb.add_torque(torque);
+		}
+		for (id, f, p) in reactions {
+			if let Some(b) = world.get_mut(id).filter(|b| b.is_dynamic()) {
+				let r = p.sub(&b.pos);
+				b.rb.add_force(f);
+				b.rb.add_torque(r.cross(&f));
+			}
+		}
+	}
+
+	// wheel spin from drive, tire and brake torque; tire torque never pushes past rolling speed
+	fn spin(spin: f32, drive: f32, tire: f32, brake: f32, i_w: f32, dt: f32, roll: Option<f32>) -> f32 {
+		let mut s = spin + (drive - tire) / i_w * dt;
+		if let Some(roll) = roll {
+			let before = spin - roll;
+			let after = s - roll;
+			if drive == 0.0 && before * after < 0.0 {
+				s = roll;
+			}
+		}
+		let b = brake / i_w * dt;
+		if s.abs() <= b { 0.0 } else { s - b * s.signum() }
+	}
+
+	// vehicle space is z up and y forward, turned onto the world's up before the chassis rotation
+	fn frame(world: &PhysicsWorld, rot: &Quat) -> Quat {
+		let g = world.cfg.gravity;
+		let up = if g.len_sq() > 1e-12 { g.mul(-1.0).nrm() } else { Vec3::UP };
+		rot.mul(&up_rot(&up))
+	}
+
+	// wheel centre and orientation for rendering
+	pub fn wheel_transform(&self, world: &PhysicsWorld, i: usize) -> Option<(Vec3, Quat)> {
+		let wheel = self.wheels.get(i)?;
+		let body = world.get(self.body)?;
+		let rot = Self::frame(world, &body.rot);
+		let up = rot.mul_vec3(&Vec3::UP);
+		let p = body.pos.add(&rot.mul_vec3(&wheel.cfg.pos)).sub(&up.mul(wheel.len));
+		let steer = Quat::from_axis_angle(&Vec3::UP, -wheel.steer);
+		let roll = Quat::from_axis_angle(&Vec3::RIGHT, wheel.angle);
+		Some((p, rot.mul(&steer).mul(&roll)))
+	}
+}
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use crate::math::{Quat, Vec3};
+use crate::spatial::{get_chunk_height, ChunkManager, ChunkPos, CHUNK_SIZE};
+use super::{narrow, AABB, Collider, ColliderType, Collision};
+
+pub const TERRAIN_ID: u64 = u64::MAX;
+
+const EPS: f32 = 1e-4;
+// most cells one ray walks, so unbounded rays through open or unloaded space still end
+const MAX_RAY_CELLS: u32 = 4096;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unloaded {
+	Solid,
+	Empty,
+	#[default]
+	Freeze,
+}
+
+impl Unloaded {
+	pub fn from_u8(v: u8) -> Self {
+		match v {
+			0 => Self::Solid,
+			1 => Self::Empty,
+			_ => Self::Freeze,
+		}
+	}
+}
+
+pub trait Voxels {
+	// None when the cell lies in a chunk that is not loaded
+	fn solid(&self, x: i32, y: i32, z: i32) -> Option<bool>;
+
+	// block id of the cell, sources without ids report solid cells as 1
+	fn block(&self, x: i32, y: i32, z: i32) -> Option<u16> {
+		self.solid(x, y, z).map(u16::from)
+	}
+}
+
+impl Voxels for ChunkManager {
+	fn solid(&self, x: i32, y: i32, z: i32) -> Option<bool> {
+		let (s, h) = (CHUNK_SIZE as i32, get_chunk_height() as i32);
+		let chunk = self.get(&ChunkPos::new(x.div_euclid(s), y.div_euclid(s), z.div_euclid(h)))?;
+		let (lx, ly, lz) = (x.rem_euclid(s), y.rem_euclid(s), z.rem_euclid(h));
+		Some(self.is_solid(chunk.get(lx as usize, ly as usize, lz as usize)))
+	}
+
+	fn block(&self, x: i32, y: i32, z: i32) -> Option<u16> {
+		let (s, h) = (CHUNK_SIZE as i32, get_chunk_height() as i32);
+		let chunk = self.get(&ChunkPos::new(x.div_euclid(s), y.div_euclid(s), z.div_euclid(h)))?;
+		Some(chunk.get(x.rem_euclid(s) as usize, y.rem_euclid(s) as usize, z.rem_euclid(h) as usize).0)
+	}
+}
+
+fn comp(v: &Vec3, k: usize) -> f32 {
+	match k {
+		0 => v.x,
+		1 => v.y,
+		_ => v.z,
+	}
+}
+
+fn set_comp(v: &mut Vec3, k: usize, val: f32) {
+	match k {
+		0 => v.x = val,
+		1 => v.y = val,
+		_ => v.z = val,
+	}
+}
+
+fn cell(p: [i32; 3]) -> Vec3 {
+	Vec3::new(p[0] as f32 + 0.5, p[1] as f32 + 0.5, p[2] as f32 + 0.5)
+}
+
+fn range(bb: &AABB, k: usize) -> (i32, i32) {
+	((comp(&bb.min, k) + EPS).floor() as i32, (comp(&bb.max, k) - EPS).floor() as i32)
+}
+
+fn solid_or(v: &dyn Voxels, p: [i32; 3], mode: Unloaded) -> bool {
+	v.solid(p[0], p[1], p[2]).unwrap_or(mode != Unloaded::Empty)
+}
+
+pub fn touches_unloaded(v: &dyn Voxels, bb: &AABB) -> bool {
+	let (x0, x1) = range(bb, 0);
+	let (y0, y1) = range(bb, 1);
+	let (z0, z1) = range(bb, 2);
+	let (s, h) = (CHUNK_SIZE as i32, get_chunk_height() as i32);
+	for cz in z0.div_euclid(h)..=z1.div_euclid(h) {
+		for cy in y0.div_euclid(s)..=y1.div_euclid(s) {
+			for cx in x0.div_euclid(s)..=x1.div_euclid(s) {
+				let p = [(cx * s).max(x0), (cy * s).max(y0), (cz * h).max(z0)];
+				if v.solid(p[0], p[1], p[2]).is_none() {
+					return true;
+				}
+			}
+		}
+	}
+	false
+}
+
+pub(crate) fn overlaps_solid(v: &dyn Voxels, bb: &AABB, mode: Unloaded) -> bool {
+	let (x0, x1) = range(bb, 0);
+	let (y0, y1) = range(bb, 1);
+	let (z0, z1) = range(bb, 2);
+	(z0..=z1).any(|z| (y0..=y1).any(|y| (x0..=x1).any(|x| solid_or(v, [x, y, z], mode))))
+}
+
+// contacts against every solid cell the collider overlaps, skipping faces shared with a solid neighbour
+pub fn voxel_contacts(v: &dyn Voxels, col: &Collider, pos: &Vec3, rot: &Quat, mode: Unloaded) -> Vec<Collision> {
+	let bb = col.aabb_rot(pos, rot);
+	let (x0, x1) = range(&bb, 0);
+	let (y0, y1) = range(&bb, 1);
+	let (z0, z1) = range(&bb, 2);
+	let unit = Collider::box_col(Vec3::ONE);
+	let rot_v = Quat::identity();
+	let mut out = Vec::new();
+	for z in z0..=z1 {
+		for y in y0..=y1 {
+			for x in x0..=x1 {
+				let p = [x, y, z];
+				if !solid_or(v, p, mode) {
+					continue;
+				}
+				let Some(c) = narrow::collide(col, pos, rot, &unit, &cell(p), &rot_v) else {
+					continue;
+				};
+				let face = c.normal.mul(-1.0);
+				let k = (0..3).max_by(|&i, &j| comp(&face, i).abs().total_cmp(&comp(&face, j).abs())).unwrap_or(2);
+				let mut n = p;
+				n[k] += if comp(&face, k) > 0.0 { 1 } else { -1 };
+				if solid_or(v, n, mode) {
+					continue;
+				}
+				out.push(c);
+			}
+		}
+	}
+	out
+}
+
+// grid walk along the ray, returns distance and the face normal of the first solid cell
+pub fn voxel_ray(v: &dyn Voxels, o: &Vec3, d: &Vec3, max_t: f32, mode: Unloaded) -> Option<(f32, Vec3)> {
+	let d = d.nrm();
+	let mut c = [o.x.floor() as i32, o.y.floor() as i32, o.z.floor() as i32];
+	if solid_or(v, c, mode) {
+		return Some((0.0, d.mul(-1.0)));
+	}
+	let mut step = [0; 3];
+	let mut t_max = [f32::INFINITY; 3];
+	let mut t_delta = [f32::INFINITY; 3];
+	for k in 0..3 {
+		let dk = comp(&d, k);
+		if dk.abs() < 1e-9 {
+			continue;
+		}
+		step[k] = if dk > 0.0 { 1 } else { -1 };
+		let edge = if dk > 0.0 { c[k] as f32 + 1.0 } else { c[k] as f32 };
+		t_max[k] = (edge - comp(o, k)) / dk;
+		t_delta[k] = 1.0 / dk.abs();
+	}
+	// max_t may be infinite, the cell cap bounds the walk either way
+	for _ in 0..MAX_RAY_CELLS {
+		let k = (0..3).min_by(|&a, &b| t_max[a].total_cmp(&t_max[b])).unwrap_or(0);
+		let t = t_max[k];
+		if t > max_t || !t.is_finite() {
+			return None;
+		}
+		c[k] += step[k];
+		t_max[k] += t_delta[k];
+		if solid_or(v, c, mode) {
+			let mut n = Vec3::ZERO;
+			set_comp(&mut n, k, -step[k] as f32);
+			return Some((t, n));
+		}
+	}
+	None
+}
+
+pub(crate) fn sweeps(col: &Collider) -> bool {
+	matches!(col.typ, ColliderType::Box | ColliderType::ConvexHull)
+}
+
+// moves bb along one axis until it meets a solid cell, returns the allowed travel and whether it was cut short
+fn sweep_axis(v: &dyn Voxels, bb: &AABB, k: usize, d: f32, mode: Unloaded) -> (f32, bool) {
+	if d == 0.0 {
+		return (0.0, false);
+	}
+	let (i, j) = ((k + 1) % 3, (k + 2) % 3);
+	let (i0, i1) = range(bb, i);
+	let (j0, j1) = range(bb, j);
+	let blocked = |c: i32| {
+		for a in i0..=i1 {
+			for b in j0..=j1 {
+				let mut p = [0; 3];
+				p[k] = c;
+				p[i] = a;
+				p[j] = b;
+				if solid_or(v, p, mode) {
+					return true;
+				}
+			}
+		}
+		false
+	};
+	if d > 0.0 {
+		let face = comp(&bb.max, k);
+		let (c0, c1) = ((face + EPS).floor() as i32, (face + d).ceil() as i32 - 1);
+		for c in c0..=c1 {
+			if blocked(c) {
+				return ((c as f32 - face).max(0.0), true);
+			}
+		}
+	} else {
+		let face = comp(&bb.min, k);
+		let (c0, c1) = ((face - EPS).ceil() as i32 - 1, (face + d).floor() as i32);
+		for c in (c1..=c0).rev() {
+			if blocked(c) {
+				return ((c as f32 + 1.0 - face).min(0.0), true);
+			}
+		}
+	}
+	(d, false)
+}
+
+// axis-separated move, up axis first so bodies land before sliding; returns displacement and per-axis hits
+pub fn sweep_aabb(v: &dyn Voxels, bb: &AABB, d: &Vec3, mode: Unloaded) -> (Vec3, [bool; 3]) {
+	let mut cur = *bb;
+	let mut moved = Vec3::ZERO;
+	let mut hit = [false; 3];
+	for k in [2, 0, 1] {
+		let (t, h) = sweep_axis(v, &cur, k, comp(d, k), mode);
+		let mut off = Vec3::ZERO;
+		set_comp(&mut off, k, t);
+		cur = AABB::new(cur.min.add(&off), cur.max.add(&off));
+		set_comp(&mut moved, k, t);
+		hit[k] = h;
+	}
+	(moved, hit)
+}
+
+pub(crate) fn hit_resp(vel: &mut Vec3, hit: [bool; 3], friction: f32, bounce: f32, bounce_vel: f32) {
+	for (k, &h) in hit.iter().enumerate() {
+		if !h {
+			continue;
+		}
+		let vk = comp(vel, k);
+		let out = if vk.abs() > bounce_vel { -vk * bounce } else { 0.0 };
+		set_comp(vel, k, 0.0);
+		let t = vel.len();
+		if t > 0.0 {
+			let keep = (t - friction * (vk - out).abs()).max(0.0) / t;
+			*vel = vel.mul(keep);
+		}
+		set_comp(vel, k, out);
+	}
+}
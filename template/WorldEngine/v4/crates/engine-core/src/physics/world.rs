@@ -0,0 +1,982 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use crate::math::{Perlin, Quat, Vec3};
+use super::broad::AabbTree;
+use super::ccd::toi_collider;
+use super::island::Islands;
+use super::joint::JointSolver;
+use super::solver::{ContactSolver, BOUNCE_VEL};
+use super::voxel::{hit_resp, overlaps_solid, sweeps};
+use super::{
+	narrow, shatter, sweep_aabb, touches_unloaded, voxel_contacts,
+	AABB, Collider, Collision, ForceField, Fracture, FractureConfig, Joint, Layers, PhysMat, RigidBody, RigidBodyType, Unloaded, Voxels, TERRAIN_ID,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Body {
+	pub id: u64,
+	pub pos: Vec3,
+	pub rot: Quat,
+	pub rb: RigidBody,
+	pub col: Option<Collider>,
+	pub prev_pos: Vec3,
+	pub prev_rot: Quat,
+}
+
+impl Body {
+	pub fn new(id: u64, pos: Vec3, rb: RigidBody) -> Self {
+		Self {
+			id,
+			pos,
+			rot: Quat::identity(),
+			rb,
+			col: None,
+			prev_pos: pos,
+			prev_rot: Quat::identity(),
+		}
+	}
+
+	pub fn with_col(mut self, col: Collider) -> Self {
+		self.col = Some(col);
+		self
+	}
+
+	pub fn with_rot(mut self, rot: Quat) -> Self {
+		self.rot = rot;
+		self.prev_rot = rot;
+		self
+	}
+
+	// drops the previous transform so a teleport doesn't get smeared by interpolation
+	pub fn snap(&mut self) {
+		self.prev_pos = self.pos;
+		self.prev_rot = self.rot;
+	}
+
+	pub fn lrp(&self, t: f32) -> (Vec3, Quat) {
+		(self.prev_pos.lrp(&self.pos, t), self.prev_rot.slerp(&self.rot, t))
+	}
+
+	pub fn upd_inertia(&mut self) {
+		if let Some(col) = &self.col {
+			let i = col.inertia(self.rb.mass);
+			self.rb.set_inertia(&i);
+		}
+	}
+
+	pub fn aabb(&self) -> Option<AABB> {
+		self.col.as_ref().map(|c| c.aabb_rot(&self.pos, &self.rot))
+	}
+
+	pub fn is_dynamic(&self) -> bool {
+		self.rb.typ == RigidBodyType::Dynamic
+	}
+
+	pub fn is_awake(&self) -> bool {
+		self.is_dynamic() && !self.rb.is_sleeping
+	}
+
+	pub fn is_trg(&self) -> bool {
+		self.col.as_ref().is_some_and(|c| c.is_trg)
+	}
+
+	fn is_moving(&self, lin: f32, ang: f32) -> bool {
+		self.rb.typ != RigidBodyType::Static
+			&& !self.rb.is_sleeping
+			&& (self.rb.vel.len_sq() > lin * lin || self.rb.ang_vel.len_sq() > ang * ang)
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PhysicsConfig {
+	pub gravity: Vec3,
+	pub restitution: f32,
+	pub friction: f32,
+	pub iterations: u32,
+	pub warm_start: bool,
+	pub baumgarte: f32,
+	pub slop: f32,
+	pub margin: f32,
+	pub ccd_enabled: bool,
+	pub ccd_vel: f32,
+	pub sleep_enabled: bool,
+	pub sleep_vel: f32,
+	pub sleep_ang_vel: f32,
+	pub sleep_time: f32,
+	pub unloaded: Unloaded,
+	pub terrain_mat: PhysMat,
+	pub fixed_dt: f32,
+	pub max_sub_steps: u32,
+}
+
+impl Default for PhysicsConfig {
+	fn default() -> Self {
+		Self {
+			gravity: Vec3::new(0.0, 0.0, -9.81),
+			restitution: 0.3,
+			friction: 0.5,
+			iterations: 10,
+			warm_start: true,
+			baumgarte: 0.2,
+			slop: 0.005,
+			margin: 0.1,
+			ccd_enabled: true,
+			ccd_vel: 10.0,
+			sleep_enabled: true,
+			sleep_vel: 0.05,
+			sleep_ang_vel: 0.05,
+			sleep_time: 0.5,
+			unloaded: Unloaded::Freeze,
+			terrain_mat: PhysMat::STONE,
+			fixed_dt: 1.0 / 60.0,
+			max_sub_steps: 4,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PhysicsEvent {
+	CollisionEnter { a: u64, b: u64 },
+	CollisionExit { a: u64, b: u64 },
+	JointBreak { id: u64, a: u64, b: u64 },
+	TriggerEnter { trg: u64, other: u64 },
+	TriggerStay { trg: u64, other: u64 },
+	TriggerExit { trg: u64, other: u64 },
+	Fracture { id: u64, first: u64, cnt: u32 },
+}
+
+// fragment ids count up from here, clear of hand-picked ones yet still exact in the f32 event buffers
+const FRAG_ID: u64 = 1 << 22;
+
+fn pair_key(a: u64, b: u64) -> (u64, u64) {
+	if a < b { (a, b) } else { (b, a) }
+}
+
+pub struct PhysicsWorld {
+	pub cfg: PhysicsConfig,
+	pub layers: Layers,
+	bodies: Vec<Body>,
+	idx: HashMap<u64, usize>,
+	contacts: Vec<Collision>,
+	terrain: Vec<Collision>,
+	frozen: HashSet<u64>,
+	touching: HashSet<(u64, u64)>,
+	overlaps: Vec<(u64, u64)>,
+	inside: HashSet<(u64, u64)>,
+	events: Vec<PhysicsEvent>,
+	solver: ContactSolver,
+	tree: AabbTree,
+	proxies: HashMap<u64, usize>,
+	joints: Vec<Joint>,
+	jsolver: JointSolver,
+	next_joint: u64,
+	no_collide: HashSet<(u64, u64)>,
+	acc: f32,
+	sub_steps: u32,
+	breakable: HashMap<u64, FractureConfig>,
+	impacts: HashMap<u64, (f32, Vec3)>,
+	fractures: Vec<Fracture>,
+	next_frag: u64,
+	fields: Vec<ForceField>,
+	next_field: u64,
+	noise: Perlin,
+	time: f32,
+}
+
+impl Default for PhysicsWorld {
+	fn default() -> Self {
+		Self::new(PhysicsConfig::default())
+	}
+}
+
+impl PhysicsWorld {
+	pub fn new(cfg: PhysicsConfig) -> Self {
+		Self {
+			cfg,
+			layers: Layers::default(),
+			bodies: Vec::new(),
+			idx: HashMap::new(),
+			contacts: Vec::new(),
+			terrain: Vec::new(),
+			frozen: HashSet::new(),
+			touching: HashSet::new(),
+			overlaps: Vec::new(),
+			inside: HashSet::new(),
+			events: Vec::new(),
+			solver: ContactSolver::default(),
+			tree: AabbTree::new(cfg.margin),
+			proxies: HashMap::new(),
+			joints: Vec::new(),
+			jsolver: JointSolver::default(),
+			next_joint: 1,
+			no_collide: HashSet::new(),
+			acc: 0.0,
+			sub_steps: 0,
+			breakable: HashMap::new(),
+			impacts: HashMap::new(),
+			fractures: Vec::new(),
+			next_frag: FRAG_ID,
+			fields: Vec::new(),
+			next_field: 1,
+			noise: Perlin::new(0),
+			time: 0.0,
+		}
+	}
+
+	pub fn set_gravity(&mut self, gravity: Vec3) {
+		self.cfg.gravity = gravity;
+	}
+
+	pub fn add(&mut self, mut body: Body) {
+		body.upd_inertia();
+		if let Some(&i) = self.idx.get(&body.id) {
+			self.bodies[i] = body;
+			self.sync_proxy(i);
+			return;
+		}
+		self.idx.insert(body.id, self.bodies.len());
+		self.bodies.push(body);
+		self.sync_proxy(self.bodies.len() - 1);
+	}
+
+	pub fn del(&mut self, id: u64) -> Option<Body> {
+		let i = self.idx.remove(&id)?;
+		let body = self.bodies.swap_remove(i);
+		if let Some(moved) = self.bodies.get(i) {
+			self.idx.insert(moved.id, i);
+		}
+		self.touching.retain(|&(a, b)| a != id && b != id);
+		self.frozen.remove(&id);
+		self.breakable.remove(&id);
+		self.inside.retain(|&(a, b)| a != id && b != id);
+		if let Some(p) = self.proxies.remove(&id) {
+			self.tree.remove(p);
+		}
+		let n = self.joints.len();
+		self.joints.retain(|j| j.a != id && j.b != id);
+		if self.joints.len() != n {
+			self.upd_no_collide();
+		}
+		Some(body)
+	}
+
+	pub fn get(&self, id: u64) -> Option<&Body> {
+		self.idx.get(&id).map(|&i| &self.bodies[i])
+	}
+
+	pub fn get_mut(&mut self, id: u64) -> Option<&mut Body> {
+		self.idx.get(&id).map(|&i| &mut self.bodies[i])
+	}
+
+	pub fn wake(&mut self, id: u64) {
+		if let Some(body) = self.get_mut(id) {
+			body.rb.wake();
+		}
+	}
+
+	pub fn awake_cnt(&self) -> usize {
+		self.bodies.iter().filter(|b| b.is_awake()).count()
+	}
+
+	pub fn has(&self, id: u64) -> bool {
+		self.idx.contains_key(&id)
+	}
+
+	pub fn bodies(&self) -> &[Body] {
+		&self.bodies
+	}
+
+	pub fn tree(&self) -> &AabbTree {
+		&self.tree
+	}
+
+	pub fn contacts(&self) -> &[Collision] {
+		&self.contacts
+	}
+
+	pub fn overlaps(&self) -> &[(u64, u64)] {
+		&self.overlaps
+	}
+
+	pub fn terrain_contacts(&self) -> &[Collision] {
+		&self.terrain
+	}
+
+	pub fn is_frozen(&self, id: u64) -> bool {
+		self.frozen.contains(&id)
+	}
+
+	pub fn drain_events(&mut self) -> Vec<PhysicsEvent> {
+		std::mem::take(&mut self.events)
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.bodies.len()
+	}
+
+	pub fn clr(&mut self) {
+		self.bodies.clear();
+		self.idx.clear();
+		self.contacts.clear();
+		self.terrain.clear();
+		self.frozen.clear();
+		self.touching.clear();
+		self.overlaps.clear();
+		self.inside.clear();
+		self.events.clear();
+		self.solver.clr();
+		self.tree.clr();
+		self.proxies.clear();
+		self.joints.clear();
+		self.jsolver.clr();
+		self.no_collide.clear();
+		self.acc = 0.0;
+		self.breakable.clear();
+		self.impacts.clear();
+		self.fractures.clear();
+		self.fields.clear();
+		self.time = 0.0;
+	}
+
+	pub fn add_joint(&mut self, mut joint: Joint) -> Option<u64> {
+		let (a, b) = (self.get(joint.a)?, self.get(joint.b)?);
+		joint.init(a, b);
+		joint.id = self.next_joint;
+		self.next_joint += 1;
+		self.wake(joint.a);
+		self.wake(joint.b);
+		let id = joint.id;
+		self.joints.push(joint);
+		self.upd_no_collide();
+		Some(id)
+	}
+
+	pub fn del_joint(&mut self, id: u64) -> Option<Joint> {
+		let i = self.joints.iter().position(|j| j.id == id)?;
+		let joint = self.joints.remove(i);
+		self.wake(joint.a);
+		self.wake(joint.b);
+		self.upd_no_collide();
+		Some(joint)
+	}
+
+	pub fn joint(&self, id: u64) -> Option<&Joint> {
+		self.joints.iter().find(|j| j.id == id)
+	}
+
+	pub fn joint_mut(&mut self, id: u64) -> Option<&mut Joint> {
+		self.joints.iter_mut().find(|j| j.id == id)
+	}
+
+	pub fn joints(&self) -> &[Joint] {
+		&self.joints
+	}
+
+	pub fn add_field(&mut self, mut field: ForceField) -> u64 {
+		field.id = self.next_field;
+		self.next_field += 1;
+		self.fields.push(field);
+		field.id
+	}
+
+	pub fn del_field(&mut self, id: u64) -> Option<ForceField> {
+		let i = self.fields.iter().position(|f| f.id == id)?;
+		Some(self.fields.remove(i))
+	}
+
+	pub fn field(&self, id: u64) -> Option<&ForceField> {
+		self.fields.iter().find(|f| f.id == id)
+	}
+
+	pub fn field_mut(&mut self, id: u64) -> Option<&mut ForceField> {
+		self.fields.iter_mut().find(|f| f.id == id)
+	}
+
+	pub fn fields(&self) -> &[ForceField] {
+		&self.fields
+	}
+
+	fn apply_fields(&mut self, dt: f32, vox: Option<&dyn Voxels>) {
+		self.time += dt;
+		if self.fields.is_empty() {
+			return;
+		}
+		let g = self.cfg.gravity;
+		for body in &mut self.bodies {
+			if !body.is_dynamic() || self.frozen.contains(&body.id) {
+				continue;
+			}
+			let (mut f, mut t) = (Vec3::ZERO, Vec3::ZERO);
+			for field in &self.fields {
+				if let Some((df, dq)) = field.force(body, &g, &self.noise, self.time, vox) {
+					f = f.add(&df);
+					t = t.add(&dq);
+				}
+			}
+			if f.len_sq() > 0.0 || t.len_sq() > 0.0 {
+				body.rb.add_force(f);
+				body.rb.add_torque(t);
+			}
+		}
+	}
+
+	pub fn set_breakable(&mut self, id: u64, cfg: FractureConfig) {
+		self.breakable.insert(id, cfg);
+	}
+
+	pub fn unset_breakable(&mut self, id: u64) -> Option<FractureConfig> {
+		self.breakable.remove(&id)
+	}
+
+	pub fn breakable(&self, id: u64) -> Option<&FractureConfig> {
+		self.breakable.get(&id)
+	}
+
+	// bodies broken during the last step
+	pub fn fractures(&self) -> &[Fracture] {
+		&self.fractures
+	}
+
+	// breaks a body now, whether or not it was marked breakable, and returns the fragment ids
+	pub fn fracture(&mut self, id: u64, point: &Vec3, impulse: f32) -> Vec<u64> {
+		let Some(body) = self.get(id) else {
+			return Vec::new();
+		};
+		let cfg = self.breakable.get(&id).copied().unwrap_or_default();
+		let frags = shatter(body, &cfg, point, self.next_frag);
+		if frags.is_empty() {
+			return Vec::new();
+		}
+		let first = self.next_frag;
+		self.next_frag += frags.len() as u64;
+		self.del(id);
+		let ids: Vec<u64> = frags.iter().map(|b| b.id).collect();
+		for frag in frags {
+			if cfg.depth > 0 {
+				self.breakable.insert(frag.id, FractureConfig { depth: cfg.depth - 1, ..cfg });
+			}
+			self.add(frag);
+		}
+		self.events.push(PhysicsEvent::Fracture { id, first, cnt: ids.len() as u32 });
+		self.fractures.push(Fracture { id, point: *point, impulse, frags: ids.clone() });
+		ids
+	}
+
+	fn upd_fractures(&mut self) {
+		let mut hits: Vec<(u64, f32, Vec3)> = self.impacts
+			.drain()
+			.filter(|(id, (j, _))| self.breakable.get(id).is_some_and(|c| *j >= c.threshold))
+			.map(|(id, (j, p))| (id, j, p))
+			.collect();
+		// hash order isn't stable, ids are
+		hits.sort_by_key(|h| h.0);
+		for (id, j, p) in hits {
+			self.fracture(id, &p, j);
+		}
+	}
+
+	pub fn local_pt(&self, id: u64, p: &Vec3) -> Vec3 {
+		match self.get(id) {
+			Some(body) => body.rot.conj().mul_vec3(&p.sub(&body.pos)),
+			None => *p,
+		}
+	}
+
+	pub fn upd_no_collide(&mut self) {
+		self.no_collide = self.joints
+			.iter()
+			.filter(|j| j.is_active() && !j.collide)
+			.map(|j| pair_key(j.a, j.b))
+			.collect();
+	}
+
+	pub fn step(&mut self, dt: f32) {
+		self.step_with(dt, None);
+	}
+
+	pub fn tick(&mut self, dt: f32) -> u32 {
+		self.tick_with(dt, None)
+	}
+
+	// runs whole fixed steps out of the accumulated frame time, the remainder feeds alpha()
+	pub fn tick_with(&mut self, dt: f32, vox: Option<&dyn Voxels>) -> u32 {
+		self.tick_each(dt, vox, |_, _| {})
+	}
+
+	// tick_with that runs pre before every fixed step, for controllers that apply forces like vehicles
+	pub fn tick_each(&mut self, dt: f32, vox: Option<&dyn Voxels>, mut pre: impl FnMut(&mut Self, f32)) -> u32 {
+		let fixed = self.cfg.fixed_dt;
+		self.sub_steps = 0;
+		if dt <= 0.0 || fixed <= 0.0 {
+			return 0;
+		}
+		self.acc += dt;
+		while self.acc >= fixed && self.sub_steps < self.cfg.max_sub_steps {
+			pre(self, fixed);
+			self.step_with(fixed, vox);
+			self.acc -= fixed;
+			self.sub_steps += 1;
+		}
+		// a hitch longer than the cap is dropped rather than carried into later frames
+		if self.acc >= fixed {
+			self.acc %= fixed;
+		}
+		self.sub_steps
+	}
+
+	pub fn alpha(&self) -> f32 {
+		if self.cfg.fixed_dt > 0.0 { (self.acc / self.cfg.fixed_dt).clamp(0.0, 1.0) } else { 1.0 }
+	}
+
+	pub fn sub_steps(&self) -> u32 {
+		self.sub_steps
+	}
+
+	pub fn interp(&self, id: u64) -> Option<(Vec3, Quat)> {
+		self.get(id).map(|b| b.lrp(self.alpha()))
+	}
+
+	pub fn step_with(&mut self, dt: f32, vox: Option<&dyn Voxels>) {
+		if dt <= 0.0 {
+			return;
+		}
+		for body in &mut self.bodies {
+			body.snap();
+		}
+		self.fractures.clear();
+		self.detect();
+		self.terrain.clear();
+		if let Some(v) = vox {
+			self.detect_terrain(v);
+		}
+		self.apply_fields(dt, vox);
+		self.integrate_vel(dt);
+		self.solve(dt);
+		self.integrate_pos(dt, vox);
+		self.upd_sleep(dt);
+		self.upd_events();
+		self.upd_trg_events();
+		self.upd_fractures();
+		// queries between steps walk the tree, so it has to follow this step's motion
+		self.sync_tree();
+	}
+
+	fn integrate_vel(&mut self, dt: f32) {
+		let gravity = self.cfg.gravity;
+		for body in &mut self.bodies {
+			body.rb.integrate(dt, &gravity, &body.rot);
+		}
+	}
+
+	fn integrate_pos(&mut self, dt: f32, vox: Option<&dyn Voxels>) {
+		let disp: Vec<(Vec3, Option<[bool; 3]>)> = (0..self.bodies.len())
+			.map(|i| {
+				let body = &self.bodies[i];
+				if body.rb.typ == RigidBodyType::Static || body.rb.is_sleeping {
+					return (Vec3::ZERO, None);
+				}
+				let mut d = body.rb.vel.mul(dt);
+				if self.wants_ccd(body) {
+					if let Some(t) = self.sweep(i, &d, dt) {
+						d = d.mul(t);
+					}
+				}
+				match (vox, &body.col) {
+					(Some(v), Some(col)) if body.is_dynamic() && !col.is_trg && sweeps(col) => {
+						let (d, hit) = sweep_aabb(v, &col.aabb_rot(&body.pos, &body.rot), &d, self.cfg.unloaded);
+						(d, Some(hit))
+					}
+					_ => (d, None),
+				}
+			})
+			.collect();
+		let terrain_mat = self.cfg.terrain_mat;
+		let unloaded = self.cfg.unloaded;
+		for (body, (d, hit)) in self.bodies.iter_mut().zip(disp) {
+			body.pos = body.pos.add(&d);
+			if let Some(hit) = hit {
+				let mat = body.col.as_ref().and_then(|c| c.mat).unwrap_or_default();
+				let (friction, bounce) = (mat.combine_friction(&terrain_mat), mat.combine_bounce(&terrain_mat));
+				hit_resp(&mut body.rb.vel, hit, friction, bounce, BOUNCE_VEL);
+			}
+			let w = body.rb.ang_vel;
+			if body.rb.typ != RigidBodyType::Static && !body.rb.is_sleeping && w.len_sq() > 0.0 {
+				let spin = Quat::new(w.x, w.y, w.z, 0.0).mul(&body.rot);
+				let q = body.rot;
+				let h = 0.5 * dt;
+				let rot = Quat::new(q.x + spin.x * h, q.y + spin.y * h, q.z + spin.z * h, q.w + spin.w * h).nrm();
+				// swept bodies only move along axes, so refuse spins that would push their box into terrain
+				if let (Some(v), Some(col), Some(_)) = (vox, &body.col, hit) {
+					if overlaps_solid(v, &col.aabb_rot(&body.pos, &rot), unloaded) {
+						body.rb.ang_vel = Vec3::ZERO;
+						continue;
+					}
+				}
+				body.rot = rot;
+			}
+		}
+	}
+
+	fn wants_ccd(&self, body: &Body) -> bool {
+		self.cfg.ccd_enabled && body.rb.ccd && body.is_dynamic() && body.rb.speed() > self.cfg.ccd_vel
+	}
+
+	fn sweep(&self, i: usize, d: &Vec3, dt: f32) -> Option<f32> {
+		let a = &self.bodies[i];
+		let col = a.col.as_ref().filter(|c| !c.is_trg)?;
+		let bb = a.aabb()?;
+		let swept = bb.merge(&AABB::new(bb.min.add(d), bb.max.add(d)));
+		let c = col.center(&a.pos, &a.rot);
+		let r = col.inner_radius();
+		let mut best: Option<f32> = None;
+		self.tree.query(&swept, |id| {
+			let Some(b) = self.idx.get(&id).map(|&j| &self.bodies[j]) else {
+				return true;
+			};
+			if b.id == a.id {
+				return true;
+			}
+			let Some(col_b) = b.col.as_ref().filter(|c| !c.is_trg && self.layers.allows(col, c)) else {
+				return true;
+			};
+			let db = if b.rb.typ == RigidBodyType::Static { Vec3::ZERO } else { b.rb.vel.mul(dt) };
+			if let Some(toi) = toi_collider(&c, r, d, col_b, &b.pos, &b.rot, &db) {
+				best = Some(best.map_or(toi.t, |t| t.min(toi.t)));
+			}
+			true
+		});
+		let len = d.len();
+		best.map(|t| (t + 2.0 * self.cfg.slop / len).min(1.0))
+	}
+
+	pub fn sync_tree(&mut self) {
+		self.tree.margin = self.cfg.margin;
+		for i in 0..self.bodies.len() {
+			self.sync_proxy(i);
+		}
+	}
+
+	pub fn refresh(&mut self, id: u64) {
+		if let Some(&i) = self.idx.get(&id) {
+			self.sync_proxy(i);
+		}
+	}
+
+	// swaps the collider, inertia follows the new shape
+	pub fn set_col(&mut self, id: u64, col: Collider) -> bool {
+		let Some(&i) = self.idx.get(&id) else {
+			return false;
+		};
+		let body = &mut self.bodies[i];
+		body.col = Some(col);
+		body.upd_inertia();
+		body.rb.wake();
+		self.sync_proxy(i);
+		true
+	}
+
+	fn sync_proxy(&mut self, i: usize) {
+		let body = &self.bodies[i];
+		match (body.aabb(), self.proxies.get(&body.id)) {
+			(Some(bb), Some(&p)) => {
+				self.tree.update(p, &bb);
+			}
+			(Some(bb), None) => {
+				self.proxies.insert(body.id, self.tree.insert(body.id, &bb));
+			}
+			(None, Some(&p)) => {
+				self.tree.remove(p);
+				self.proxies.remove(&body.id);
+			}
+			(None, None) => {}
+		}
+	}
+
+	fn queries(body: &Body) -> bool {
+		body.is_awake() || body.rb.typ == RigidBodyType::Kinematic
+	}
+
+	fn pairs(&self) -> Vec<(usize, usize)> {
+		let mut pairs = Vec::new();
+		let mut hits = Vec::new();
+		for (i, body) in self.bodies.iter().enumerate() {
+			if !Self::queries(body) {
+				continue;
+			}
+			let Some(&p) = self.proxies.get(&body.id) else {
+				continue;
+			};
+			hits.clear();
+			self.tree.query(&self.tree.fat(p), |id| {
+				hits.push(id);
+				true
+			});
+			for id in &hits {
+				let Some(&j) = self.idx.get(id) else {
+					continue;
+				};
+				if j == i || (j < i && Self::queries(&self.bodies[j])) {
+					continue;
+				}
+				let (Some(col_a), Some(col_b)) = (&body.col, &self.bodies[j].col) else {
+					continue;
+				};
+				if !self.layers.allows(col_a, col_b) {
+					continue;
+				}
+				pairs.push((i.min(j), i.max(j)));
+			}
+		}
+		pairs.sort_unstable();
+		pairs.dedup();
+		pairs
+	}
+
+	fn detect(&mut self) {
+		self.contacts.clear();
+		self.overlaps.clear();
+		self.sync_tree();
+		for (i, j) in self.pairs() {
+			let (a, b) = (&self.bodies[i], &self.bodies[j]);
+			let (Some(col_a), Some(col_b)) = (&a.col, &b.col) else {
+				continue;
+			};
+			let trg = col_a.is_trg || col_b.is_trg;
+			if !trg && (!a.is_dynamic() && !b.is_dynamic() || self.no_collide.contains(&pair_key(a.id, b.id))) {
+				continue;
+			}
+			if !col_a.aabb_rot(&a.pos, &a.rot).intersects(&col_b.aabb_rot(&b.pos, &b.rot)) {
+				continue;
+			}
+			let Some(mut col) = narrow::collide(col_a, &a.pos, &a.rot, col_b, &b.pos, &b.rot) else {
+				continue;
+			};
+			if trg {
+				self.overlaps.push(if col_a.is_trg { (a.id, b.id) } else { (b.id, a.id) });
+				continue;
+			}
+			col.ent_a = a.id;
+			col.ent_b = b.id;
+			self.contacts.push(col);
+		}
+		self.wake_touched();
+	}
+
+	fn detect_terrain(&mut self, v: &dyn Voxels) {
+		let freeze = self.cfg.unloaded == Unloaded::Freeze;
+		let thawed: Vec<u64> = self.frozen
+			.iter()
+			.copied()
+			.filter(|&id| self.get(id).and_then(|b| b.aabb()).is_none_or(|bb| !freeze || !touches_unloaded(v, &bb)))
+			.collect();
+		for id in thawed {
+			self.frozen.remove(&id);
+			self.wake(id);
+		}
+		for body in &mut self.bodies {
+			if !body.is_awake() {
+				continue;
+			}
+			let Some(col) = body.col.as_ref().filter(|c| !c.is_trg) else {
+				continue;
+			};
+			if freeze && touches_unloaded(v, &col.aabb_rot(&body.pos, &body.rot)) {
+				body.rb.sleep();
+				self.frozen.insert(body.id);
+				continue;
+			}
+			if sweeps(col) {
+				continue;
+			}
+			for mut c in voxel_contacts(v, col, &body.pos, &body.rot, self.cfg.unloaded) {
+				c.ent_a = body.id;
+				c.ent_b = TERRAIN_ID;
+				self.terrain.push(c);
+			}
+		}
+	}
+
+	fn wake_touched(&mut self) {
+		let (lin, ang) = (self.cfg.sleep_vel, self.cfg.sleep_ang_vel);
+		for c in 0..self.contacts.len() {
+			let (Some(&a), Some(&b)) = (self.idx.get(&self.contacts[c].ent_a), self.idx.get(&self.contacts[c].ent_b)) else {
+				continue;
+			};
+			if self.bodies[a].rb.is_sleeping && self.bodies[b].is_moving(lin, ang) {
+				self.bodies[a].rb.wake();
+			}
+			if self.bodies[b].rb.is_sleeping && self.bodies[a].is_moving(lin, ang) {
+				self.bodies[b].rb.wake();
+			}
+		}
+		for j in self.joints.iter().filter(|j| j.is_active()) {
+			let (Some(&a), Some(&b)) = (self.idx.get(&j.a), self.idx.get(&j.b)) else {
+				continue;
+			};
+			if self.bodies[a].rb.is_sleeping && self.bodies[b].is_moving(lin, ang) {
+				self.bodies[a].rb.wake();
+			}
+			if self.bodies[b].rb.is_sleeping && self.bodies[a].is_moving(lin, ang) {
+				self.bodies[b].rb.wake();
+			}
+		}
+	}
+
+	fn upd_sleep(&mut self, dt: f32) {
+		if !self.cfg.sleep_enabled {
+			for body in &mut self.bodies {
+				if body.rb.is_sleeping {
+					body.rb.wake();
+				}
+			}
+			return;
+		}
+		let (lin, ang) = (self.cfg.sleep_vel, self.cfg.sleep_ang_vel);
+		for body in &mut self.bodies {
+			if !body.is_awake() {
+				continue;
+			}
+			if body.is_moving(lin, ang) {
+				body.rb.sleep_timer = 0.0;
+			} else {
+				body.rb.sleep_timer += dt;
+			}
+		}
+		let n = self.bodies.len();
+		let mut isl = Islands::new(n);
+		let mut pinned = vec![false; n];
+		for col in &self.contacts {
+			let (Some(&a), Some(&b)) = (self.idx.get(&col.ent_a), self.idx.get(&col.ent_b)) else {
+				continue;
+			};
+			match (self.bodies[a].is_dynamic(), self.bodies[b].is_dynamic()) {
+				(true, true) => isl.union(a, b),
+				(true, false) => pinned[a] |= self.bodies[b].is_moving(lin, ang),
+				(false, true) => pinned[b] |= self.bodies[a].is_moving(lin, ang),
+				(false, false) => {}
+			}
+		}
+		for j in self.joints.iter().filter(|j| j.is_active()) {
+			let (Some(&a), Some(&b)) = (self.idx.get(&j.a), self.idx.get(&j.b)) else {
+				continue;
+			};
+			match (self.bodies[a].is_dynamic(), self.bodies[b].is_dynamic()) {
+				(true, true) => isl.union(a, b),
+				(true, false) => pinned[a] |= self.bodies[b].is_moving(lin, ang),
+				(false, true) => pinned[b] |= self.bodies[a].is_moving(lin, ang),
+				(false, false) => {}
+			}
+		}
+		let mut ready = vec![true; n];
+		for (i, body) in self.bodies.iter().enumerate() {
+			if !body.is_dynamic() {
+				continue;
+			}
+			let root = isl.find(i);
+			if pinned[i] || (!body.rb.is_sleeping && body.rb.sleep_timer < self.cfg.sleep_time) {
+				ready[root] = false;
+			}
+		}
+		for (i, body) in self.bodies.iter_mut().enumerate() {
+			if !body.is_dynamic() {
+				continue;
+			}
+			let ready = ready[isl.find(i)];
+			if ready && !body.rb.is_sleeping {
+				body.rb.sleep();
+			} else if !ready && body.rb.is_sleeping {
+				body.rb.wake();
+			}
+		}
+	}
+
+	fn solve(&mut self, dt: f32) {
+		self.solver.prepare(&self.bodies, &self.idx, self.contacts.iter().chain(&self.terrain), &self.cfg, dt);
+		self.jsolver.prepare(&self.bodies, &self.idx, &self.joints, &self.cfg, dt);
+		if self.cfg.warm_start {
+			self.jsolver.warm(&mut self.bodies);
+			self.solver.warm(&mut self.bodies);
+		}
+		for _ in 0..self.cfg.iterations {
+			self.jsolver.solve(&mut self.bodies);
+			self.solver.solve(&mut self.bodies);
+		}
+		if !self.breakable.is_empty() {
+			for (a, b, j, p) in self.solver.impulses(&self.bodies) {
+				for id in [a, b] {
+					if self.breakable.contains_key(&id) {
+						// impulses add up, the break point is their weighted middle
+						let e = self.impacts.entry(id).or_insert((0.0, p));
+						let sum = e.0 + j;
+						if sum > 0.0 {
+							*e = (sum, e.1.mul(e.0 / sum).add(&p.mul(j / sum)));
+						}
+					}
+				}
+			}
+		}
+		self.solver.store();
+		let broke = self.jsolver.store(&mut self.joints, dt);
+		for &i in &broke {
+			let j = &self.joints[i];
+			self.events.push(PhysicsEvent::JointBreak { id: j.id, a: j.a, b: j.b });
+		}
+		if !broke.is_empty() {
+			self.upd_no_collide();
+		}
+	}
+
+	fn upd_events(&mut self) {
+		let mut now: HashSet<(u64, u64)> = self.contacts
+			.iter()
+			.map(|c| pair_key(c.ent_a, c.ent_b))
+			.collect();
+		for &(a, b) in &self.touching {
+			let resting = |id: u64| {
+				self.get(id).is_some_and(|body| body.rb.is_sleeping || body.rb.typ == RigidBodyType::Static)
+			};
+			if resting(a) && resting(b) {
+				now.insert((a, b));
+			}
+		}
+		let mut enter: Vec<(u64, u64)> = now.difference(&self.touching).copied().collect();
+		let mut exit: Vec<(u64, u64)> = self.touching.difference(&now).copied().collect();
+		enter.sort_unstable();
+		exit.sort_unstable();
+		for (a, b) in enter {
+			self.events.push(PhysicsEvent::CollisionEnter { a, b });
+		}
+		for (a, b) in exit {
+			self.events.push(PhysicsEvent::CollisionExit { a, b });
+		}
+		self.touching = now;
+	}
+
+	fn upd_trg_events(&mut self) {
+		let resting = |id: u64| {
+			self.get(id).is_some_and(|body| body.rb.is_sleeping || body.rb.typ == RigidBodyType::Static)
+		};
+		let mut now: HashSet<(u64, u64)> = self.overlaps.iter().copied().collect();
+		for &(trg, other) in &self.inside {
+			if resting(trg) && resting(other) {
+				now.insert((trg, other));
+			}
+		}
+		let mut enter: Vec<(u64, u64)> = now.difference(&self.inside).copied().collect();
+		let mut stay: Vec<(u64, u64)> = now.intersection(&self.inside).copied().collect();
+		let mut exit: Vec<(u64, u64)> = self.inside.difference(&now).copied().collect();
+		enter.sort_unstable();
+		stay.sort_unstable();
+		exit.sort_unstable();
+		for (trg, other) in enter {
+			self.events.push(PhysicsEvent::TriggerEnter { trg, other });
+		}
+		for (trg, other) in stay {
+			self.events.push(PhysicsEvent::TriggerStay { trg, other });
+		}
+		for (trg, other) in exit {
+			self.events.push(PhysicsEvent::TriggerExit { trg, other });
+		}
+		self.inside = now;
+	}
+}
@@ -1,6 +1,17 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv(mut h: u64, bytes: &[u8]) -> u64 {
+	for &b in bytes {
+		h ^= b as u64;
+		h = h.wrapping_mul(FNV_PRIME);
+	}
+	h
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
 	pub tick: u64,
@@ -36,6 +47,18 @@ impl Snapshot {
 	pub fn size(&self) -> usize {
 		self.data.values().map(|v| v.len()).sum()
 	}
+
+	pub fn checksum(&self) -> u64 {
+		let mut keys: Vec<&String> = self.data.keys().collect();
+		keys.sort();
+		let mut h = fnv(FNV_OFFSET, &self.tick.to_le_bytes());
+		for key in keys {
+			h = fnv(h, key.as_bytes());
+			h = fnv(h, &(self.data[key].len() as u64).to_le_bytes());
+			h = fnv(h, &self.data[key]);
+		}
+		h
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::math::Rng;
 
 pub const CHUNK_SIZE: usize = 32;
 pub const CHUNK_HEIGHT: usize = 64;
@@ -45,6 +46,10 @@ impl ChunkPos {
 		)
 	}
 
+	pub fn rng(&self, world: &Rng) -> Rng {
+		world.fork3(self.x, self.y, self.z)
+	}
+
 	pub fn neighbors(&self) -> [ChunkPos; 26] {
 		let mut res = [ChunkPos::new(0, 0, 0); 26];
 		let mut idx = 0;
@@ -131,6 +136,8 @@ impl ChunkData {
 pub struct ChunkManager {
 	chunks: HashMap<ChunkPos, ChunkData>,
 	load_dist: i32,
+	// blocks like water that fill a cell without blocking movement
+	non_solid: HashSet<u16>,
 }
 
 impl Default for ChunkManager {
@@ -144,9 +151,22 @@ impl ChunkManager {
 		Self {
 			chunks: HashMap::new(),
 			load_dist,
+			non_solid: HashSet::new(),
 		}
 	}
 
+	pub fn set_solid(&mut self, block: BlockId, solid: bool) {
+		if solid {
+			self.non_solid.remove(&block.0);
+		} else {
+			self.non_solid.insert(block.0);
+		}
+	}
+
+	pub fn is_solid(&self, block: BlockId) -> bool {
+		block.0 != 0 && !self.non_solid.contains(&block.0)
+	}
+
 	pub fn get(&self, pos: &ChunkPos) -> Option<&ChunkData> {
 		self.chunks.get(pos)
 	}
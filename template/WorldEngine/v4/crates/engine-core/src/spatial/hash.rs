@@ -0,0 +1,75 @@
+use crate::math::Vec3;
+
+// dense hash over a uniform grid, rebuilt in one counting-sort pass from a point list
+#[derive(Debug, Clone, Default)]
+pub struct SpatialHash {
+	pub spacing: f32,
+	start: Vec<u32>,
+	ids: Vec<u32>,
+}
+
+impl SpatialHash {
+	pub fn new(spacing: f32) -> Self {
+		Self {
+			spacing,
+			start: Vec::new(),
+			ids: Vec::new(),
+		}
+	}
+
+	fn cell(&self, p: &Vec3) -> (i32, i32, i32) {
+		let inv = 1.0 / self.spacing;
+		((p.x * inv).floor() as i32, (p.y * inv).floor() as i32, (p.z * inv).floor() as i32)
+	}
+
+	fn slot(&self, c: (i32, i32, i32)) -> usize {
+		let h = (c.0.wrapping_mul(92837111)) ^ (c.1.wrapping_mul(689287499)) ^ (c.2.wrapping_mul(283923481));
+		h.unsigned_abs() as usize % (self.start.len() - 1)
+	}
+
+	pub fn build(&mut self, pts: &[Vec3]) {
+		let size = (pts.len() * 2).max(1);
+		self.start.clear();
+		self.start.resize(size + 1, 0);
+		self.ids.clear();
+		self.ids.resize(pts.len(), 0);
+		let slots: Vec<usize> = pts.iter().map(|p| self.slot(self.cell(p))).collect();
+		for &s in &slots {
+			self.start[s] += 1;
+		}
+		let mut acc = 0;
+		for c in self.start.iter_mut() {
+			acc += *c;
+			*c = acc;
+		}
+		for (i, &s) in slots.iter().enumerate() {
+			self.start[s] -= 1;
+			self.ids[self.start[s] as usize] = i as u32;
+		}
+	}
+
+	// every point in cells overlapping the cube of half-size r, callers still need a distance check
+	pub fn query<F: FnMut(usize)>(&self, p: &Vec3, r: f32, mut f: F) {
+		if self.start.len() < 2 {
+			return;
+		}
+		let lo = self.cell(&p.sub(&Vec3::new(r, r, r)));
+		let hi = self.cell(&p.add(&Vec3::new(r, r, r)));
+		let mut seen: Vec<usize> = Vec::new();
+		for z in lo.2..=hi.2 {
+			for y in lo.1..=hi.1 {
+				for x in lo.0..=hi.0 {
+					let s = self.slot((x, y, z));
+					// distinct cells can share a slot, visit it once
+					if seen.contains(&s) {
+						continue;
+					}
+					seen.push(s);
+					for &i in &self.ids[self.start[s] as usize..self.start[s + 1] as usize] {
+						f(i as usize);
+					}
+				}
+			}
+		}
+	}
+}
@@ -1,5 +1,7 @@
 mod octree;
 mod chunk;
+mod hash;
 
 pub use octree::{Octree, OctreeNode};
-pub use chunk::{ChunkPos, ChunkData, ChunkManager, set_chunk_height, get_chunk_height, CHUNK_SIZE, CHUNK_HEIGHT};
+pub use hash::SpatialHash;
+pub use chunk::{BlockId, ChunkPos, ChunkData, ChunkManager, set_chunk_height, get_chunk_height, CHUNK_SIZE, CHUNK_HEIGHT};
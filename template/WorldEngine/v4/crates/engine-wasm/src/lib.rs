@@ -1,13 +1,12 @@
 use wasm_bindgen::prelude::*;
 use engine_core::{
-	math::{Vec3, Mat4, Quat, Perlin},
+	math::{Vec3, Mat4, Quat, Perlin, NoiseGrid, Rng},
 	ecs::{World, EntityId},
-	physics::{AABB, Collider, RigidBody, test_aabb_aabb, test_sphere_sphere},
-	spatial::{ChunkPos, ChunkData, ChunkManager, CHUNK_SIZE, CHUNK_HEIGHT, set_chunk_height},
+	physics::{AABB, Body, CharController, Collider, CombRule, DebugDraw, FieldKind, FieldShape, Fluid, FluidConfig, ForceField, FractureConfig, Joint, Motor, PhysMat, PhysicsEvent, PhysicsWorld, RayHit, RigidBody, SoftBody, SoftConfig, Unloaded, Vehicle, VehicleConfig, WheelConfig, stiffness_compliance, sweep_pairs, test_aabb_aabb, test_sphere_sphere},
+	spatial::{BlockId, ChunkPos, ChunkData, ChunkManager, CHUNK_SIZE, CHUNK_HEIGHT, set_chunk_height},
 	pathfind::{PathFinder, PathNode},
 	snapshot::{Snapshot, SnapshotBuffer},
 };
-use std::collections::HashMap;
 
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -151,6 +150,106 @@ impl WasmPerlin {
 	pub fn fbm3d(&self, x: f32, y: f32, z: f32, octaves: u32, persistence: f32) -> f32 {
 		self.inner.fbm3d(x, y, z, octaves, persistence)
 	}
+
+	// one z slice at z = 0, matching noise2d
+	pub fn grid2d(&self, origin: &[f32], size: &[u32], step: f32, octaves: u32, persistence: f32) -> Vec<f32> {
+		let o = |i: usize| origin.get(i).copied().unwrap_or(0.0);
+		let s = |i: usize| size.get(i).copied().unwrap_or(1) as usize;
+		let grid = NoiseGrid::new2d(o(0), o(1), step, s(0), s(1)).with_fbm(octaves, persistence);
+		self.inner.sample_grid(&grid)
+	}
+
+	pub fn grid3d(&self, origin: &[f32], size: &[u32], step: f32, octaves: u32, persistence: f32) -> Vec<f32> {
+		self.inner.sample_grid(&Self::mk_grid(origin, size, step, octaves, persistence))
+	}
+
+	pub fn fill_grid(&self, out: &mut [f32], origin: &[f32], size: &[u32], step: f32, octaves: u32, persistence: f32) {
+		self.inner.fill(&Self::mk_grid(origin, size, step, octaves, persistence), out);
+	}
+
+	fn mk_grid(origin: &[f32], size: &[u32], step: f32, octaves: u32, persistence: f32) -> NoiseGrid {
+		let o = |i: usize| origin.get(i).copied().unwrap_or(0.0);
+		let s = |i: usize| size.get(i).copied().unwrap_or(1) as usize;
+		NoiseGrid::new3d(Vec3::new(o(0), o(1), o(2)), step, s(0), s(1), s(2))
+			.with_fbm(octaves, persistence)
+	}
+}
+
+#[wasm_bindgen]
+pub struct WasmRng {
+	inner: Rng,
+}
+
+#[wasm_bindgen]
+impl WasmRng {
+	#[wasm_bindgen(constructor)]
+	pub fn new(seed: u64) -> Self {
+		Self { inner: Rng::new(seed) }
+	}
+
+	pub fn split(&mut self) -> WasmRng {
+		WasmRng { inner: self.inner.split() }
+	}
+
+	pub fn fork(&self, key: u64) -> WasmRng {
+		WasmRng { inner: self.inner.fork(key) }
+	}
+
+	pub fn fork_chunk(&self, cx: i32, cy: i32, cz: i32) -> WasmRng {
+		WasmRng { inner: ChunkPos::new(cx, cy, cz).rng(&self.inner) }
+	}
+
+	pub fn next_u32(&mut self) -> u32 {
+		self.inner.next_u32()
+	}
+
+	pub fn next_f32(&mut self) -> f32 {
+		self.inner.next_f32()
+	}
+
+	pub fn next_bool(&mut self, p: f32) -> bool {
+		self.inner.next_bool(p)
+	}
+
+	pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+		self.inner.range_f32(lo, hi)
+	}
+
+	pub fn range_i32(&mut self, lo: i32, hi: i32) -> i32 {
+		self.inner.range_i32(lo, hi)
+	}
+
+	pub fn normal(&mut self, mean: f32, std_dev: f32) -> f32 {
+		self.inner.normal(mean, std_dev)
+	}
+
+	pub fn weighted(&mut self, weights: &[f32]) -> i32 {
+		self.inner.weighted(weights).map(|i| i as i32).unwrap_or(-1)
+	}
+
+	pub fn shuffle(&mut self, cnt: usize) -> Vec<u32> {
+		let mut idx: Vec<u32> = (0..cnt as u32).collect();
+		self.inner.shuffle(&mut idx);
+		idx
+	}
+
+	pub fn in_sphere(&mut self, r: f32) -> Vec<f32> {
+		let p = self.inner.in_sphere(r);
+		vec![p.x, p.y, p.z]
+	}
+
+	pub fn on_sphere(&mut self, r: f32) -> Vec<f32> {
+		let p = self.inner.on_sphere(r);
+		vec![p.x, p.y, p.z]
+	}
+
+	pub fn state(&self) -> String {
+		serde_json::to_string(&self.inner).unwrap_or_default()
+	}
+
+	pub fn from_state(json: &str) -> Option<WasmRng> {
+		serde_json::from_str(json).ok().map(|inner| WasmRng { inner })
+	}
 }
 
 #[wasm_bindgen]
@@ -215,10 +314,15 @@ impl WasmChunkManager {
 
 	pub fn set_block(&mut self, cx: i32, cy: i32, cz: i32, lx: usize, ly: usize, lz: usize, block: u16) {
 		if let Some(chunk) = self.inner.get_mut(&ChunkPos::new(cx, cy, cz)) {
-			chunk.set(lx, ly, lz, engine_core::spatial::chunk::BlockId(block));
+			chunk.set(lx, ly, lz, BlockId(block));
 		}
 	}
 
+	// non-solid blocks such as water stay visible to block queries but bodies pass through them
+	pub fn set_solid(&mut self, block: u16, solid: bool) {
+		self.inner.set_solid(BlockId(block), solid);
+	}
+
 	pub fn upd_around(&mut self, cx: i32, cy: i32, cz: i32) -> Vec<i32> {
 		let loaded = self.inner.upd_around(ChunkPos::new(cx, cy, cz));
 		loaded.iter().flat_map(|p| [p.x, p.y, p.z]).collect()
@@ -339,31 +443,34 @@ pub fn col_sphere(
 #[wasm_bindgen]
 pub fn batch_aabb_test(data: &[f32]) -> Vec<u8> {
 	let cnt = data.len() / 12;
-	let mut results = Vec::with_capacity(cnt * cnt / 8 + 1);
-	for _ in 0..((cnt * cnt + 7) / 8) {
-		results.push(0u8);
-	}
-	for i in 0..cnt {
-		let ai = i * 12;
-		let a = AABB::new(
-			Vec3::new(data[ai], data[ai + 1], data[ai + 2]),
-			Vec3::new(data[ai + 3], data[ai + 4], data[ai + 5])
-		);
-		for j in (i + 1)..cnt {
-			let bi = j * 12;
-			let b = AABB::new(
-				Vec3::new(data[bi], data[bi + 1], data[bi + 2]),
-				Vec3::new(data[bi + 3], data[bi + 4], data[bi + 5])
-			);
-			if a.intersects(&b) {
-				let idx = i * cnt + j;
-				results[idx / 8] |= 1 << (idx % 8);
-			}
-		}
+	let mut results = vec![0u8; (cnt * cnt).div_ceil(8)];
+	let aabbs: Vec<AABB> = data
+		.chunks_exact(12)
+		.map(|d| AABB::new(Vec3::new(d[0], d[1], d[2]), Vec3::new(d[3], d[4], d[5])))
+		.collect();
+	for (i, j) in sweep_pairs(&aabbs) {
+		let idx = i * cnt + j;
+		results[idx / 8] |= 1 << (idx % 8);
 	}
 	results
 }
 
+fn hit_arr(h: &RayHit) -> [f32; 8] {
+	[h.id as f32, h.point.x, h.point.y, h.point.z, h.normal.x, h.normal.y, h.normal.z, h.dist]
+}
+
+// None for arrays shorter than three, so bad js input can't trap the module
+fn to_vec3(v: &[f32]) -> Option<Vec3> {
+	match v {
+		[x, y, z, ..] => Some(Vec3::new(*x, *y, *z)),
+		_ => None,
+	}
+}
+
+fn to_vec3s(pts: &[f32]) -> Vec<Vec3> {
+	pts.chunks_exact(3).map(|p| Vec3::new(p[0], p[1], p[2])).collect()
+}
+
 #[wasm_bindgen]
 pub struct WasmCollider {
 	inner: Collider,
@@ -383,6 +490,10 @@ impl WasmCollider {
 		WasmCollider { inner: Collider::capsule(r, h) }
 	}
 
+	pub fn convex_hull(pts: &[f32]) -> Option<WasmCollider> {
+		Collider::convex_hull(&to_vec3s(pts)).map(|inner| WasmCollider { inner })
+	}
+
 	pub fn set_offset(&mut self, x: f32, y: f32, z: f32) {
 		self.inner.offset = Vec3::new(x, y, z);
 	}
@@ -400,114 +511,639 @@ impl WasmCollider {
 
 #[wasm_bindgen]
 pub struct WasmPhysicsWorld {
-	bodies: HashMap<u64, (Vec3, Vec3, Vec3, f32, bool)>,
-	colliders: HashMap<u64, Collider>,
-	gravity: Vec3,
+	inner: PhysicsWorld,
+	dbg: DebugDraw,
 }
 
 #[wasm_bindgen]
 impl WasmPhysicsWorld {
 	#[wasm_bindgen(constructor)]
 	pub fn new() -> Self {
-		Self {
-			bodies: HashMap::new(),
-			colliders: HashMap::new(),
-			gravity: Vec3::new(0.0, -9.81, 0.0),
-		}
+		let mut inner = PhysicsWorld::default();
+		inner.set_gravity(Vec3::new(0.0, -9.81, 0.0));
+		Self { inner, dbg: DebugDraw::default() }
 	}
 
 	pub fn set_gravity(&mut self, x: f32, y: f32, z: f32) {
-		self.gravity = Vec3::new(x, y, z);
+		self.inner.set_gravity(Vec3::new(x, y, z));
+	}
+
+	pub fn set_restitution(&mut self, e: f32) {
+		self.inner.cfg.restitution = e;
+	}
+
+	pub fn set_friction(&mut self, f: f32) {
+		self.inner.cfg.friction = f;
+	}
+
+	pub fn set_iterations(&mut self, n: u32) {
+		self.inner.cfg.iterations = n;
+	}
+
+	pub fn set_warm_start(&mut self, on: bool) {
+		self.inner.cfg.warm_start = on;
+	}
+
+	pub fn set_margin(&mut self, m: f32) {
+		self.inner.cfg.margin = m;
+	}
+
+	pub fn set_ccd_cfg(&mut self, enabled: bool, vel: f32) {
+		self.inner.cfg.ccd_enabled = enabled;
+		self.inner.cfg.ccd_vel = vel;
+	}
+
+	pub fn set_ccd(&mut self, id: u64, on: bool) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.ccd = on;
+		}
+	}
+
+	pub fn set_material(&mut self, id: u64, friction: f32, bounce: f32, friction_rule: u8, bounce_rule: u8) {
+		let mat = PhysMat::new(friction, bounce, CombRule::from_u8(friction_rule), CombRule::from_u8(bounce_rule));
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.mat = Some(mat);
+		}
+	}
+
+	pub fn set_material_preset(&mut self, id: u64, name: &str) -> bool {
+		let Some(mat) = PhysMat::preset(name) else {
+			return false;
+		};
+		match self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			Some(col) => {
+				col.mat = Some(mat);
+				true
+			}
+			None => false,
+		}
 	}
 
 	pub fn add_body(&mut self, id: u64, mass: f32) {
-		self.bodies.insert(id, (Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, mass, false));
+		let rb = if mass > 0.0 { RigidBody::dyn_body(mass) } else { RigidBody::stc() };
+		let col = self.inner.get(id).and_then(|b| b.col.clone());
+		let mut body = Body::new(id, Vec3::ZERO, rb);
+		body.col = col;
+		self.inner.add(body);
+	}
+
+	pub fn add_kinematic(&mut self, id: u64) {
+		let col = self.inner.get(id).and_then(|b| b.col.clone());
+		let mut body = Body::new(id, Vec3::ZERO, RigidBody::kinematic());
+		body.col = col;
+		self.inner.add(body);
 	}
 
 	pub fn add_box_col(&mut self, id: u64, sx: f32, sy: f32, sz: f32) {
-		self.colliders.insert(id, Collider::box_col(Vec3::new(sx, sy, sz)));
+		self.set_col(id, Collider::box_col(Vec3::new(sx, sy, sz)));
 	}
 
 	pub fn add_sphere_col(&mut self, id: u64, r: f32) {
-		self.colliders.insert(id, Collider::sphere(r));
+		self.set_col(id, Collider::sphere(r));
+	}
+
+	pub fn add_capsule_col(&mut self, id: u64, r: f32, h: f32) {
+		self.set_col(id, Collider::capsule(r, h));
+	}
+
+	pub fn add_hull_col(&mut self, id: u64, pts: &[f32]) -> bool {
+		let Some(col) = Collider::convex_hull(&to_vec3s(pts)) else {
+			return false;
+		};
+		self.set_col(id, col);
+		true
+	}
+
+	fn set_col(&mut self, id: u64, col: Collider) {
+		if self.inner.get(id).is_none() {
+			self.inner.add(Body::new(id, Vec3::ZERO, RigidBody::stc()).with_col(col));
+		} else {
+			self.inner.set_col(id, col);
+		}
 	}
 
 	pub fn set_pos(&mut self, id: u64, x: f32, y: f32, z: f32) {
-		if let Some(body) = self.bodies.get_mut(&id) {
-			body.0 = Vec3::new(x, y, z);
+		if let Some(body) = self.inner.get_mut(id) {
+			body.pos = Vec3::new(x, y, z);
+			body.snap();
 		}
+		self.inner.refresh(id);
 	}
 
 	pub fn set_vel(&mut self, id: u64, x: f32, y: f32, z: f32) {
-		if let Some(body) = self.bodies.get_mut(&id) {
-			body.1 = Vec3::new(x, y, z);
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.vel = Vec3::new(x, y, z);
+			body.rb.wake();
+		}
+	}
+
+	pub fn wake(&mut self, id: u64) {
+		self.inner.wake(id);
+	}
+
+	pub fn is_sleeping(&self, id: u64) -> bool {
+		self.inner.get(id).is_some_and(|b| b.rb.is_sleeping)
+	}
+
+	pub fn set_sleep_cfg(&mut self, enabled: bool, vel: f32, time: f32) {
+		self.inner.cfg.sleep_enabled = enabled;
+		self.inner.cfg.sleep_vel = vel;
+		self.inner.cfg.sleep_ang_vel = vel;
+		self.inner.cfg.sleep_time = time;
+	}
+
+	pub fn awake_cnt(&self) -> usize {
+		self.inner.awake_cnt()
+	}
+
+	pub fn add_force(&mut self, id: u64, x: f32, y: f32, z: f32) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.add_force(Vec3::new(x, y, z));
+		}
+	}
+
+	pub fn add_impulse(&mut self, id: u64, x: f32, y: f32, z: f32) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.add_impulse(Vec3::new(x, y, z));
 		}
 	}
 
+	pub fn add_impulse_at(&mut self, id: u64, imp: &[f32], point: &[f32]) {
+		let (Some(imp), Some(point)) = (to_vec3(imp), to_vec3(point)) else {
+			return;
+		};
+		if let Some(body) = self.inner.get_mut(id) {
+			let r = point.sub(&body.pos);
+			body.rb.add_impulse_at(&imp, &r, &body.rot);
+		}
+	}
+
+	pub fn add_torque(&mut self, id: u64, x: f32, y: f32, z: f32) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.add_torque(Vec3::new(x, y, z));
+		}
+	}
+
+	pub fn set_rot(&mut self, id: u64, x: f32, y: f32, z: f32, w: f32) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rot = Quat::new(x, y, z, w).nrm();
+			body.snap();
+		}
+		self.inner.refresh(id);
+	}
+
+	pub fn set_ang_vel(&mut self, id: u64, x: f32, y: f32, z: f32) {
+		if let Some(body) = self.inner.get_mut(id) {
+			body.rb.ang_vel = Vec3::new(x, y, z);
+			body.rb.wake();
+		}
+	}
+
+	pub fn get_rots(&self) -> Vec<f32> {
+		let mut result = Vec::with_capacity(self.inner.cnt() * 8);
+		for body in self.inner.bodies() {
+			let q = body.rot;
+			let w = body.rb.ang_vel;
+			result.extend_from_slice(&[body.id as f32, q.x, q.y, q.z, q.w, w.x, w.y, w.z]);
+		}
+		result
+	}
+
 	pub fn remove(&mut self, id: u64) {
-		self.bodies.remove(&id);
-		self.colliders.remove(&id);
+		self.inner.del(id);
 	}
 
 	pub fn step(&mut self, dt: f32) -> Vec<f32> {
-		let ids: Vec<u64> = self.bodies.keys().copied().collect();
-		for &id in &ids {
-			if let Some(body) = self.bodies.get_mut(&id) {
-				if body.3 > 0.0 {
-					body.1.x += self.gravity.x * dt;
-					body.1.y += self.gravity.y * dt;
-					body.1.z += self.gravity.z * dt;
-					body.0.x += body.1.x * dt;
-					body.0.y += body.1.y * dt;
-					body.0.z += body.1.z * dt;
-				}
-			}
+		self.inner.tick(dt);
+		self.collisions()
+	}
+
+	pub fn step_terrain(&mut self, dt: f32, chunks: &WasmChunkManager) -> Vec<f32> {
+		self.inner.tick_with(dt, Some(&chunks.inner));
+		self.collisions()
+	}
+
+	pub fn set_tps(&mut self, tps: f32) {
+		if tps > 0.0 {
+			self.inner.cfg.fixed_dt = 1.0 / tps;
 		}
-		let mut collisions = Vec::new();
-		for i in 0..ids.len() {
-			for j in (i + 1)..ids.len() {
-				let id_a = ids[i];
-				let id_b = ids[j];
-				if let (Some(col_a), Some(col_b)) = (self.colliders.get(&id_a), self.colliders.get(&id_b)) {
-					if let (Some(body_a), Some(body_b)) = (self.bodies.get(&id_a), self.bodies.get(&id_b)) {
-						let aabb_a = col_a.aabb(&body_a.0);
-						let aabb_b = col_b.aabb(&body_b.0);
-						if let Some(col) = test_aabb_aabb(&aabb_a, &aabb_b) {
-							collisions.push(id_a as f32);
-							collisions.push(id_b as f32);
-							collisions.push(col.normal.x);
-							collisions.push(col.normal.y);
-							collisions.push(col.normal.z);
-							collisions.push(col.depth);
-						}
-					}
-				}
-			}
+	}
+
+	pub fn set_max_sub_steps(&mut self, n: u32) {
+		self.inner.cfg.max_sub_steps = n.max(1);
+	}
+
+	pub fn alpha(&self) -> f32 {
+		self.inner.alpha()
+	}
+
+	pub fn sub_steps(&self) -> u32 {
+		self.inner.sub_steps()
+	}
+
+	pub fn get_interp(&self) -> Vec<f32> {
+		let t = self.inner.alpha();
+		let mut result = Vec::with_capacity(self.inner.cnt() * 8);
+		for body in self.inner.bodies() {
+			let (p, q) = body.lrp(t);
+			result.extend_from_slice(&[body.id as f32, p.x, p.y, p.z, q.x, q.y, q.z, q.w]);
+		}
+		result
+	}
+
+	pub fn get_transforms(&self) -> Vec<f32> {
+		let mut result = Vec::with_capacity(self.inner.cnt() * 15);
+		for body in self.inner.bodies() {
+			let (p0, q0, p1, q1) = (body.prev_pos, body.prev_rot, body.pos, body.rot);
+			result.extend_from_slice(&[body.id as f32, p0.x, p0.y, p0.z, q0.x, q0.y, q0.z, q0.w, p1.x, p1.y, p1.z, q1.x, q1.y, q1.z, q1.w]);
+		}
+		result
+	}
+
+	pub fn set_unloaded_mode(&mut self, mode: u8) {
+		self.inner.cfg.unloaded = Unloaded::from_u8(mode);
+	}
+
+	pub fn set_terrain_mat(&mut self, friction: f32, bounce: f32) {
+		self.inner.cfg.terrain_mat = PhysMat::new(friction, bounce, CombRule::Avg, CombRule::Avg);
+	}
+
+	pub fn is_frozen(&self, id: u64) -> bool {
+		self.inner.is_frozen(id)
+	}
+
+	fn collisions(&self) -> Vec<f32> {
+		// a frame that ran no fixed step would report the last step's contacts again
+		if self.inner.sub_steps() == 0 {
+			return Vec::new();
+		}
+		let mut collisions = Vec::with_capacity(self.inner.contacts().len() * 6);
+		for col in self.inner.contacts() {
+			collisions.push(col.ent_a as f32);
+			collisions.push(col.ent_b as f32);
+			collisions.push(col.normal.x);
+			collisions.push(col.normal.y);
+			collisions.push(col.normal.z);
+			collisions.push(col.depth);
 		}
 		collisions
 	}
 
-	pub fn get_states(&self) -> Vec<f32> {
-		let mut result = Vec::with_capacity(self.bodies.len() * 7);
-		for (&id, body) in &self.bodies {
+	pub fn drain_events(&mut self) -> Vec<f32> {
+		let events = self.inner.drain_events();
+		let mut result = Vec::with_capacity(events.len() * 4);
+		for ev in events {
+			let (typ, a, b, id) = match ev {
+				PhysicsEvent::CollisionEnter { a, b } => (0.0, a, b, 0),
+				PhysicsEvent::CollisionExit { a, b } => (1.0, a, b, 0),
+				PhysicsEvent::JointBreak { id, a, b } => (2.0, a, b, id),
+				PhysicsEvent::TriggerEnter { trg, other } => (3.0, trg, other, 0),
+				PhysicsEvent::TriggerStay { trg, other } => (4.0, trg, other, 0),
+				PhysicsEvent::TriggerExit { trg, other } => (5.0, trg, other, 0),
+				PhysicsEvent::Fracture { id, first, cnt } => (6.0, id, first, cnt as u64),
+			};
+			result.push(typ);
+			result.push(a as f32);
+			result.push(b as f32);
 			result.push(id as f32);
-			result.push(body.0.x);
-			result.push(body.0.y);
-			result.push(body.0.z);
-			result.push(body.1.x);
-			result.push(body.1.y);
-			result.push(body.1.z);
+		}
+		result
+	}
+
+	// flags pick categories: 1 shapes, 2 aabbs, 4 contacts, 8 joints, 16 sleep, 32 broadphase tree
+	pub fn debug_draw(&mut self, flags: u32) -> usize {
+		self.dbg.flags = flags;
+		self.dbg.draw(&self.inner);
+		self.dbg.line_cnt()
+	}
+
+	// interleaved x y z r g b, two vertices per line
+	pub fn debug_lines(&self) -> Vec<f32> {
+		self.dbg.lines.clone()
+	}
+
+	pub fn debug_points(&self) -> Vec<f32> {
+		self.dbg.points.clone()
+	}
+
+	fn anchors(&self, a: u64, b: u64, pa: &[f32], pb: &[f32]) -> Option<(Vec3, Vec3)> {
+		let (pa, pb) = (to_vec3(pa)?, to_vec3(pb)?);
+		Some((self.inner.local_pt(a, &pa), self.inner.local_pt(b, &pb)))
+	}
+
+	pub fn add_dist_joint(&mut self, a: u64, b: u64, pa: &[f32], pb: &[f32], min: f32, max: f32) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pa, pb) else {
+			return 0;
+		};
+		self.inner.add_joint(Joint::dist(a, b, la, lb, max).with_range(min, max)).unwrap_or(0)
+	}
+
+	pub fn add_spring_joint(&mut self, a: u64, b: u64, pts: &[f32], rest: f32, stiffness: f32, damping: f32) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pts, pts.get(3..).unwrap_or_default()) else {
+			return 0;
+		};
+		self.inner.add_joint(Joint::spring(a, b, la, lb, rest, stiffness, damping)).unwrap_or(0)
+	}
+
+	pub fn add_ball_joint(&mut self, a: u64, b: u64, pt: &[f32]) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pt, pt) else {
+			return 0;
+		};
+		self.inner.add_joint(Joint::ball(a, b, la, lb)).unwrap_or(0)
+	}
+
+	pub fn add_hinge_joint(&mut self, a: u64, b: u64, pt: &[f32], axis: &[f32]) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pt, pt) else {
+			return 0;
+		};
+		let (Some(axis), Some(ba), Some(bb)) = (to_vec3(axis), self.inner.get(a), self.inner.get(b)) else {
+			return 0;
+		};
+		let (axis_a, axis_b) = (ba.rot.conj().mul_vec3(&axis), bb.rot.conj().mul_vec3(&axis));
+		self.inner.add_joint(Joint::hinge(a, b, la, lb, axis_a, axis_b)).unwrap_or(0)
+	}
+
+	pub fn add_slider_joint(&mut self, a: u64, b: u64, pt: &[f32], axis: &[f32]) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pt, pt) else {
+			return 0;
+		};
+		let (Some(axis), Some(ba)) = (to_vec3(axis), self.inner.get(a)) else {
+			return 0;
+		};
+		let axis = ba.rot.conj().mul_vec3(&axis);
+		self.inner.add_joint(Joint::slider(a, b, la, lb, axis)).unwrap_or(0)
+	}
+
+	pub fn add_fixed_joint(&mut self, a: u64, b: u64, pt: &[f32]) -> u64 {
+		let Some((la, lb)) = self.anchors(a, b, pt, pt) else {
+			return 0;
+		};
+		self.inner.add_joint(Joint::fixed(a, b, la, lb)).unwrap_or(0)
+	}
+
+	pub fn del_joint(&mut self, id: u64) -> bool {
+		self.inner.del_joint(id).is_some()
+	}
+
+	pub fn set_joint_range(&mut self, id: u64, lo: f32, hi: f32) {
+		if let Some(j) = self.inner.joint_mut(id) {
+			j.set_range(lo, hi);
+		}
+	}
+
+	pub fn set_joint_motor(&mut self, id: u64, spd: f32, max: f32) {
+		if let Some(j) = self.inner.joint_mut(id) {
+			j.set_motor((max > 0.0).then_some(Motor { spd, max }));
+		}
+	}
+
+	pub fn set_joint_break(&mut self, id: u64, force: f32, torque: f32) {
+		if let Some(j) = self.inner.joint_mut(id) {
+			j.break_force = force;
+			j.break_torque = torque;
+		}
+	}
+
+	pub fn add_buoyancy(&mut self, min: &[f32], max: &[f32], density: f32, drag: f32) -> u64 {
+		let (Some(min), Some(max)) = (to_vec3(min), to_vec3(max)) else {
+			return 0;
+		};
+		self.add_buoyancy_field(FieldShape::Aabb(AABB::new(min, max)), density, drag)
+	}
+
+	// buoyancy in every terrain cell holding this block id
+	pub fn add_buoyancy_blocks(&mut self, block: u16, density: f32, drag: f32) -> u64 {
+		self.add_buoyancy_field(FieldShape::Blocks(block), density, drag)
+	}
+
+	fn add_buoyancy_field(&mut self, shape: FieldShape, density: f32, drag: f32) -> u64 {
+		let mut f = ForceField::buoyancy(shape, density);
+		if let FieldKind::Buoyancy { drag: d, ang_drag, .. } = &mut f.kind {
+			*d = drag;
+			*ang_drag = drag;
+		}
+		self.inner.add_field(f)
+	}
+
+	pub fn add_wind(&mut self, min: &[f32], max: &[f32], vel: &[f32], turbulence: f32) -> u64 {
+		let (Some(min), Some(max), Some(vel)) = (to_vec3(min), to_vec3(max), to_vec3(vel)) else {
+			return 0;
+		};
+		let mut f = ForceField::wind(FieldShape::Aabb(AABB::new(min, max)), vel);
+		if let FieldKind::Wind { turbulence: t, .. } = &mut f.kind {
+			*t = turbulence;
+		}
+		self.inner.add_field(f)
+	}
+
+	// negative strength repels
+	pub fn add_attractor(&mut self, x: f32, y: f32, z: f32, radius: f32, strength: f32) -> u64 {
+		let center = Vec3::new(x, y, z);
+		self.inner.add_field(ForceField::attractor(FieldShape::Sphere { center, radius }, center, strength))
+	}
+
+	pub fn add_vortex(&mut self, center: &[f32], radius: f32, axis: &[f32], strength: f32, pull: f32, lift: f32) -> u64 {
+		let (Some(c), Some(axis)) = (to_vec3(center), to_vec3(axis)) else {
+			return 0;
+		};
+		let mut f = ForceField::vortex(FieldShape::Sphere { center: c, radius }, c, axis, strength);
+		if let FieldKind::Vortex { pull: p, lift: l, .. } = &mut f.kind {
+			*p = pull;
+			*l = lift;
+		}
+		self.inner.add_field(f)
+	}
+
+	pub fn del_field(&mut self, id: u64) -> bool {
+		self.inner.del_field(id).is_some()
+	}
+
+	pub fn set_field_enabled(&mut self, id: u64, on: bool) {
+		if let Some(f) = self.inner.field_mut(id) {
+			f.enabled = on;
+		}
+	}
+
+	pub fn set_field_mask(&mut self, id: u64, mask: u32) {
+		if let Some(f) = self.inner.field_mut(id) {
+			f.mask = mask;
+		}
+	}
+
+	pub fn set_breakable(&mut self, id: u64, min_fragments: u32, max_fragments: u32, threshold: f32, depth: u32) {
+		self.inner.set_breakable(id, FractureConfig { min_fragments, max_fragments, threshold, depth, seed: id, ..Default::default() });
+	}
+
+	pub fn unset_breakable(&mut self, id: u64) {
+		self.inner.unset_breakable(id);
+	}
+
+	// breaks the body at a world point now, returns the fragment ids
+	pub fn fracture(&mut self, id: u64, x: f32, y: f32, z: f32, impulse: f32) -> Vec<f32> {
+		self.inner.fracture(id, &Vec3::new(x, y, z), impulse).into_iter().map(|id| id as f32).collect()
+	}
+
+	// hull vertices in body space, empty for boxes and round shapes
+	pub fn hull_verts(&self, id: u64) -> Vec<f32> {
+		self.inner.get(id)
+			.and_then(|b| b.col.as_ref())
+			.and_then(|c| c.hull.as_ref())
+			.map(|h| h.verts.iter().flat_map(|v| [v.x, v.y, v.z]).collect())
+			.unwrap_or_default()
+	}
+
+	pub fn hull_indices(&self, id: u64) -> Vec<u32> {
+		self.inner.get(id)
+			.and_then(|b| b.col.as_ref())
+			.and_then(|c| c.hull.as_ref())
+			.map(|h| h.faces.iter().flatten().copied().collect())
+			.unwrap_or_default()
+	}
+
+	pub fn set_joint_enabled(&mut self, id: u64, on: bool) {
+		if let Some(j) = self.inner.joint_mut(id) {
+			j.enabled = on;
+		}
+		self.inner.upd_no_collide();
+	}
+
+	pub fn is_joint_broken(&self, id: u64) -> bool {
+		self.inner.joint(id).is_some_and(|j| j.broken)
+	}
+
+	pub fn joint_angle(&self, id: u64) -> f32 {
+		let Some(j) = self.inner.joint(id) else {
+			return 0.0;
+		};
+		match (self.inner.get(j.a), self.inner.get(j.b)) {
+			(Some(a), Some(b)) => j.angle(a, b),
+			_ => 0.0,
+		}
+	}
+
+	pub fn get_states(&self) -> Vec<f32> {
+		let mut result = Vec::with_capacity(self.inner.cnt() * 7);
+		for body in self.inner.bodies() {
+			result.push(body.id as f32);
+			result.push(body.pos.x);
+			result.push(body.pos.y);
+			result.push(body.pos.z);
+			result.push(body.rb.vel.x);
+			result.push(body.rb.vel.y);
+			result.push(body.rb.vel.z);
+		}
+		result
+	}
+
+	pub fn raycast(&self, o: &[f32], d: &[f32], max_dist: f32, mask: u32) -> Vec<f32> {
+		let (Some(o), Some(d)) = (to_vec3(o), to_vec3(d)) else {
+			return Vec::new();
+		};
+		self.inner
+			.raycast(&o, &d, max_dist, mask)
+			.map_or_else(Vec::new, |h| hit_arr(&h).to_vec())
+	}
+
+	pub fn raycast_all(&self, o: &[f32], d: &[f32], max_dist: f32, mask: u32) -> Vec<f32> {
+		let (Some(o), Some(d)) = (to_vec3(o), to_vec3(d)) else {
+			return Vec::new();
+		};
+		self.inner
+			.raycast_all(&o, &d, max_dist, mask)
+			.iter()
+			.flat_map(hit_arr)
+			.collect()
+	}
+
+	pub fn sphere_cast(&self, o: &[f32], r: f32, d: &[f32], max_dist: f32, mask: u32) -> Vec<f32> {
+		let (Some(o), Some(d)) = (to_vec3(o), to_vec3(d)) else {
+			return Vec::new();
+		};
+		self.inner
+			.sphere_cast(&o, r, &d, max_dist, mask)
+			.map_or_else(Vec::new, |h| hit_arr(&h).to_vec())
+	}
+
+	pub fn overlap_sphere(&self, x: f32, y: f32, z: f32, r: f32, mask: u32) -> Vec<f32> {
+		self.inner
+			.overlap_sphere(&Vec3::new(x, y, z), r, mask)
+			.into_iter()
+			.map(|id| id as f32)
+			.collect()
+	}
+
+	pub fn overlap_aabb(&self, min: &[f32], max: &[f32], mask: u32) -> Vec<f32> {
+		let (Some(min), Some(max)) = (to_vec3(min), to_vec3(max)) else {
+			return Vec::new();
+		};
+		self.inner
+			.overlap_aabb(&AABB::new(min, max), mask)
+			.into_iter()
+			.map(|id| id as f32)
+			.collect()
+	}
+
+	pub fn set_layer(&mut self, id: u64, layer: u8) {
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.layer = layer;
+		}
+	}
+
+	pub fn set_group(&mut self, id: u64, group: i32) {
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.group = group;
+		}
+	}
+
+	pub fn set_mask(&mut self, id: u64, mask: u32) {
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.mask = Some(mask);
+		}
+	}
+
+	pub fn clr_mask(&mut self, id: u64) {
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.mask = None;
+		}
+	}
+
+	pub fn set_layer_name(&mut self, layer: u8, name: &str) {
+		self.inner.layers.set_name(layer, name);
+	}
+
+	pub fn layer_id(&self, name: &str) -> i32 {
+		self.inner.layers.find(name).map_or(-1, |l| l as i32)
+	}
+
+	pub fn set_layer_collides(&mut self, a: u8, b: u8, on: bool) {
+		self.inner.layers.set(a, b, on);
+	}
+
+	pub fn layer_mask(&self, layer: u8) -> u32 {
+		self.inner.layers.mask(layer)
+	}
+
+	pub fn set_trg(&mut self, id: u64, is_trg: bool) {
+		if let Some(col) = self.inner.get_mut(id).and_then(|b| b.col.as_mut()) {
+			col.is_trg = is_trg;
+		}
+	}
+
+	pub fn get_overlaps(&self) -> Vec<f32> {
+		let mut result = Vec::with_capacity(self.inner.overlaps().len() * 2);
+		for &(trg, other) in self.inner.overlaps() {
+			result.push(trg as f32);
+			result.push(other as f32);
 		}
 		result
 	}
 
 	pub fn cnt(&self) -> usize {
-		self.bodies.len()
+		self.inner.cnt()
 	}
 
 	pub fn clr(&mut self) {
-		self.bodies.clear();
-		self.colliders.clear();
+		self.inner.clr();
 	}
 }
 
@@ -543,3 +1179,369 @@ pub fn batch_find_path(
 	}
 	results
 }
+
+#[wasm_bindgen]
+pub struct WasmCharController {
+	inner: CharController,
+}
+
+#[wasm_bindgen]
+impl WasmCharController {
+	#[wasm_bindgen(constructor)]
+	pub fn new(x: f32, y: f32, z: f32, r: f32, h: f32) -> Self {
+		Self { inner: CharController::new(Vec3::new(x, y, z), r, h) }
+	}
+
+	#[wasm_bindgen(js_name = "move")]
+	pub fn mv(&mut self, world: &mut WasmPhysicsWorld, vx: f32, vy: f32, vz: f32, dt: f32) -> Vec<f32> {
+		let d = self.inner.mv(&mut world.inner, None, &Vec3::new(vx, vy, vz), dt);
+		vec![d.x, d.y, d.z]
+	}
+
+	pub fn move_terrain(&mut self, world: &mut WasmPhysicsWorld, chunks: &WasmChunkManager, vx: f32, vy: f32, vz: f32, dt: f32) -> Vec<f32> {
+		let d = self.inner.mv(&mut world.inner, Some(&chunks.inner), &Vec3::new(vx, vy, vz), dt);
+		vec![d.x, d.y, d.z]
+	}
+
+	pub fn pos(&self) -> Vec<f32> {
+		vec![self.inner.pos.x, self.inner.pos.y, self.inner.pos.z]
+	}
+
+	pub fn set_pos(&mut self, x: f32, y: f32, z: f32) {
+		self.inner.pos = Vec3::new(x, y, z);
+		self.inner.vel = Vec3::ZERO;
+	}
+
+	pub fn vel(&self) -> Vec<f32> {
+		vec![self.inner.vel.x, self.inner.vel.y, self.inner.vel.z]
+	}
+
+	pub fn grounded(&self) -> bool {
+		self.inner.grounded
+	}
+
+	pub fn ground_n(&self) -> Vec<f32> {
+		vec![self.inner.ground_n.x, self.inner.ground_n.y, self.inner.ground_n.z]
+	}
+
+	pub fn set_body(&mut self, id: u64) {
+		self.inner.body = Some(id);
+	}
+
+	pub fn set_step_h(&mut self, h: f32) {
+		self.inner.cfg.step_h = h;
+	}
+
+	pub fn set_max_slope(&mut self, deg: f32) {
+		self.inner.cfg.max_slope = deg.to_radians();
+	}
+
+	pub fn set_snap(&mut self, dist: f32) {
+		self.inner.cfg.snap = dist;
+	}
+
+	pub fn set_push(&mut self, push: f32) {
+		self.inner.cfg.push = push;
+	}
+
+	pub fn set_mask(&mut self, mask: u32) {
+		self.inner.cfg.mask = mask;
+	}
+}
+
+#[wasm_bindgen]
+pub struct WasmSoftBody {
+	inner: SoftBody,
+}
+
+#[wasm_bindgen]
+impl WasmSoftBody {
+	pub fn rope(start: &[f32], end: &[f32], segs: usize, mass: f32) -> Option<WasmSoftBody> {
+		let (a, b) = (to_vec3(start)?, to_vec3(end)?);
+		Some(Self { inner: SoftBody::rope(a, b, segs, mass, SoftConfig::default()) })
+	}
+
+	pub fn cloth(origin: &[f32], u: &[f32], v: &[f32], seg_x: usize, seg_y: usize, mass: f32) -> Option<WasmSoftBody> {
+		let (o, u, v) = (to_vec3(origin)?, to_vec3(u)?, to_vec3(v)?);
+		Some(Self { inner: SoftBody::cloth(o, u, v, seg_x, seg_y, mass, SoftConfig::default()) })
+	}
+
+	pub fn step(&mut self, world: &WasmPhysicsWorld, dt: f32) -> usize {
+		self.inner.step(&world.inner, None, dt)
+	}
+
+	pub fn step_terrain(&mut self, world: &WasmPhysicsWorld, chunks: &WasmChunkManager, dt: f32) -> usize {
+		self.inner.step(&world.inner, Some(&chunks.inner), dt)
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.inner.cnt()
+	}
+
+	pub fn positions(&self) -> Vec<f32> {
+		self.inner.positions()
+	}
+
+	pub fn indices(&self) -> Vec<u32> {
+		self.inner.indices()
+	}
+
+	pub fn pin(&mut self, i: usize) {
+		if let Some(p) = self.inner.ps.get(i) {
+			let at = p.pos;
+			self.inner.pin(i, at);
+		}
+	}
+
+	pub fn pin_at(&mut self, i: usize, x: f32, y: f32, z: f32) {
+		self.inner.pin(i, Vec3::new(x, y, z));
+	}
+
+	// attaches at the particle's current spot on the body
+	pub fn pin_to(&mut self, i: usize, world: &WasmPhysicsWorld, body: u64) {
+		if let Some(p) = self.inner.ps.get(i) {
+			let at = world.inner.local_pt(body, &p.pos);
+			self.inner.pin_to(i, Some(body), at);
+		}
+	}
+
+	pub fn unpin(&mut self, i: usize) {
+		self.inner.unpin(i);
+	}
+
+	pub fn set_iterations(&mut self, n: u32) {
+		self.inner.cfg.iterations = n;
+	}
+
+	pub fn set_substeps(&mut self, n: u32) {
+		self.inner.cfg.substeps = n;
+	}
+
+	pub fn set_stiffness(&mut self, stretch: f32, bend: f32) {
+		self.inner.set_compliance(stiffness_compliance(stretch), stiffness_compliance(bend));
+	}
+
+	pub fn set_tear(&mut self, threshold: f32) {
+		self.inner.cfg.tear = threshold;
+	}
+
+	pub fn set_wind(&mut self, x: f32, y: f32, z: f32, drag: f32) {
+		self.inner.cfg.wind = Vec3::new(x, y, z);
+		self.inner.cfg.drag = drag;
+	}
+
+	pub fn set_damping(&mut self, d: f32) {
+		self.inner.cfg.damping = d;
+	}
+
+	pub fn set_radius(&mut self, r: f32) {
+		self.inner.cfg.radius = r;
+	}
+
+	pub fn set_friction(&mut self, f: f32) {
+		self.inner.cfg.friction = f;
+	}
+
+	pub fn set_mask(&mut self, mask: u32) {
+		self.inner.cfg.mask = mask;
+	}
+}
+
+#[wasm_bindgen]
+pub struct WasmFluid {
+	inner: Fluid,
+}
+
+#[wasm_bindgen]
+impl WasmFluid {
+	// gravity starts out as the world's so water falls the same way as bodies
+	#[wasm_bindgen(constructor)]
+	pub fn new(world: &WasmPhysicsWorld, radius: f32, density: f32, max_particles: usize) -> Self {
+		let gravity = world.inner.cfg.gravity;
+		Self { inner: Fluid::new(FluidConfig { radius, density, max_particles, gravity, ..Default::default() }) }
+	}
+
+	pub fn set_viscosity(&mut self, v: f32) {
+		self.inner.cfg.viscosity = v;
+	}
+
+	pub fn set_surface_tension(&mut self, k: f32) {
+		self.inner.cfg.surface_tension = k;
+	}
+
+	pub fn set_relax(&mut self, relax: f32) {
+		self.inner.cfg.relax = relax;
+	}
+
+	pub fn set_iterations(&mut self, n: u32) {
+		self.inner.cfg.iterations = n;
+	}
+
+	pub fn set_gravity(&mut self, x: f32, y: f32, z: f32) {
+		self.inner.cfg.gravity = Vec3::new(x, y, z);
+	}
+
+	pub fn set_bounds(&mut self, min: &[f32], max: &[f32]) -> bool {
+		let (Some(min), Some(max)) = (to_vec3(min), to_vec3(max)) else {
+			return false;
+		};
+		self.inner.cfg.bounds = Some(AABB::new(min, max));
+		true
+	}
+
+	pub fn clr_bounds(&mut self) {
+		self.inner.cfg.bounds = None;
+	}
+
+	pub fn set_unloaded_mode(&mut self, mode: u8) {
+		self.inner.cfg.unloaded = Unloaded::from_u8(mode);
+	}
+
+	pub fn add(&mut self, x: f32, y: f32, z: f32, vx: f32, vy: f32, vz: f32) -> i32 {
+		self.inner.add(Vec3::new(x, y, z), Vec3::new(vx, vy, vz)).map_or(-1, |i| i as i32)
+	}
+
+	pub fn fill(&mut self, min: &[f32], max: &[f32]) -> usize {
+		let (Some(min), Some(max)) = (to_vec3(min), to_vec3(max)) else {
+			return 0;
+		};
+		self.inner.fill(&AABB::new(min, max), Vec3::ZERO)
+	}
+
+	pub fn clr(&mut self) {
+		self.inner.clr();
+	}
+
+	pub fn cnt(&self) -> usize {
+		self.inner.cnt()
+	}
+
+	pub fn step(&mut self, dt: f32) {
+		self.inner.step(None, dt);
+	}
+
+	pub fn step_terrain(&mut self, chunks: &WasmChunkManager, dt: f32) {
+		self.inner.step(Some(&chunks.inner), dt);
+	}
+
+	pub fn positions(&self) -> Vec<f32> {
+		self.inner.positions()
+	}
+
+	pub fn velocities(&self) -> Vec<f32> {
+		self.inner.velocities()
+	}
+
+	pub fn densities(&self) -> Vec<f32> {
+		self.inner.rho.clone()
+	}
+}
+
+#[wasm_bindgen]
+pub struct WasmVehicle {
+	inner: Vehicle,
+}
+
+#[wasm_bindgen]
+impl WasmVehicle {
+	#[wasm_bindgen(constructor)]
+	pub fn new(body: u64) -> Self {
+		Self { inner: Vehicle::new(body, VehicleConfig::default()) }
+	}
+
+	// four wheel car with default wheels, front steering and rear drive
+	pub fn car(body: u64, w: f32, l: f32, h: f32, radius: f32) -> Self {
+		Self { inner: Vehicle::car(body, VehicleConfig::default(), w, l, h, WheelConfig { radius, ..Default::default() }) }
+	}
+
+	pub fn add_wheel(&mut self, x: f32, y: f32, z: f32, radius: f32, steer: bool, drive: bool) -> usize {
+		self.inner.add_wheel(WheelConfig { pos: Vec3::new(x, y, z), radius, steer, drive, handbrake: drive, ..Default::default() })
+	}
+
+	pub fn set_suspension(&mut self, rest: f32, stiffness: f32, damping: f32) {
+		for w in &mut self.inner.wheels {
+			w.cfg.rest = rest;
+			w.cfg.stiffness = stiffness;
+			w.cfg.damping = damping;
+		}
+	}
+
+	pub fn set_grip(&mut self, grip: f32) {
+		self.inner.cfg.grip = grip;
+	}
+
+	pub fn set_engine(&mut self, torque: f32, max_rpm: f32) {
+		self.inner.cfg.engine_torque = torque;
+		self.inner.cfg.max_rpm = max_rpm;
+	}
+
+	pub fn set_gears(&mut self, gears: &[f32], reverse: f32, final_drive: f32) {
+		self.inner.cfg.gears = gears.to_vec();
+		self.inner.cfg.reverse = reverse;
+		self.inner.cfg.final_drive = final_drive;
+	}
+
+	pub fn set_auto(&mut self, auto: bool) {
+		self.inner.cfg.auto = auto;
+	}
+
+	pub fn set_brakes(&mut self, brake: f32, handbrake: f32) {
+		self.inner.cfg.brake_torque = brake;
+		self.inner.cfg.handbrake_torque = handbrake;
+	}
+
+	pub fn set_max_steer(&mut self, deg: f32) {
+		self.inner.cfg.max_steer = deg.to_radians();
+	}
+
+	pub fn set_mask(&mut self, mask: u32) {
+		self.inner.cfg.mask = mask;
+	}
+
+	pub fn set_input(&mut self, throttle: f32, brake: f32, steer: f32, handbrake: bool) {
+		self.inner.set_input(throttle, brake, steer, handbrake);
+	}
+
+	pub fn shift(&mut self, gear: i32) {
+		self.inner.shift(gear);
+	}
+
+	// ticks the world in place of world.step so the vehicle drives every fixed substep
+	pub fn step(&mut self, world: &mut WasmPhysicsWorld, dt: f32) -> Vec<f32> {
+		let v = &mut self.inner;
+		world.inner.tick_each(dt, None, |w, h| v.step(w, None, h));
+		world.collisions()
+	}
+
+	pub fn step_terrain(&mut self, world: &mut WasmPhysicsWorld, chunks: &WasmChunkManager, dt: f32) -> Vec<f32> {
+		let (v, vox) = (&mut self.inner, &chunks.inner);
+		world.inner.tick_each(dt, Some(vox), |w, h| v.step(w, Some(vox), h));
+		world.collisions()
+	}
+
+	pub fn gear(&self) -> i32 {
+		self.inner.gear
+	}
+
+	pub fn rpm(&self) -> f32 {
+		self.inner.rpm
+	}
+
+	pub fn speed(&self) -> f32 {
+		self.inner.speed
+	}
+
+	pub fn wheel_cnt(&self) -> usize {
+		self.inner.wheels.len()
+	}
+
+	// per wheel: x y z qx qy qz qw contact
+	pub fn wheels(&self, world: &WasmPhysicsWorld) -> Vec<f32> {
+		let mut out = Vec::with_capacity(self.inner.wheels.len() * 8);
+		for (i, w) in self.inner.wheels.iter().enumerate() {
+			let (p, q) = self.inner.wheel_transform(&world.inner, i).unwrap_or((Vec3::ZERO, Quat::identity()));
+			out.extend_from_slice(&[p.x, p.y, p.z, q.x, q.y, q.z, q.w, if w.contact { 1.0 } else { 0.0 }]);
+		}
+		out
+	}
+}